@@ -0,0 +1,91 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use shiren6_core::bouncy_walls::cell::{CellKind, Cells};
+
+// the search before the packed masks, for comparison
+#[allow(dead_code)]
+#[path = "../src/bouncy_walls/reference.rs"]
+mod reference;
+
+use reference::Reference;
+
+// A square floor with a wall around it, scattered walls and rows of bouncy walls, generated
+// the same way on every run.
+fn floor(size: usize) -> Cells {
+	let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+	let mut kinds = Vec::with_capacity(size * size);
+
+	for y in 0..size {
+		for x in 0..size {
+			seed ^= seed << 13;
+			seed ^= seed >> 7;
+			seed ^= seed << 17;
+
+			let kind = if y == 0 || x == 0 || y == size - 1 || x == size - 1 {
+				CellKind::BouncyWall
+			} else {
+				match seed % 16 {
+					0 | 1 => CellKind::Wall,
+					2 => CellKind::BouncyWall,
+					3 => CellKind::Water,
+					_ => CellKind::Vacant,
+				}
+			};
+			kinds.push(kind);
+		}
+	}
+
+	Cells::from_kinds(size, size, kinds).unwrap()
+}
+
+fn to_reference(cells: &Cells) -> Reference {
+	let (height, width) = cells.get_size();
+	let solid = cells.kinds().iter().map(|kind| kind.is_solid()).collect();
+	let bouncy = cells
+		.kinds()
+		.iter()
+		.map(|&kind| kind == CellKind::BouncyWall)
+		.collect();
+	Reference::new(height, width, solid, bouncy)
+}
+
+fn components(c: &mut Criterion) {
+	let mut group = c.benchmark_group("components");
+
+	for size in [24, 48, 64] {
+		let cells = floor(size);
+		group.bench_with_input(BenchmarkId::new("masks", size), &cells, |b, cells| {
+			b.iter(|| black_box(cells).components())
+		});
+		let reference = to_reference(&cells);
+		group.bench_with_input(
+			BenchmarkId::new("baseline", size),
+			&reference,
+			|b, reference| b.iter(|| black_box(reference).components()),
+		);
+	}
+
+	group.finish();
+}
+
+fn trace(c: &mut Criterion) {
+	let mut group = c.benchmark_group("trace");
+
+	for size in [24, 48, 64] {
+		let cells = floor(size);
+		let v = size + 1;
+		group.bench_with_input(BenchmarkId::new("masks", size), &cells, |b, cells| {
+			b.iter(|| black_box(cells).trace(v))
+		});
+		let reference = to_reference(&cells);
+		group.bench_with_input(
+			BenchmarkId::new("baseline", size),
+			&reference,
+			|b, reference| b.iter(|| black_box(reference).trace(v)),
+		);
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, components, trace);
+criterion_main!(benches);
@@ -0,0 +1,256 @@
+//! Checks the item tables in `src/price_list` and turns them into the static `CATEGORIES`, so
+//! that a mistake in a CSV fails the build instead of panicking in the browser.
+//!
+//! `categories.csv` lists one category per line as `id,name,table,variation,states,sell`:
+//!
+//! - `table` is the CSV file of its items, whose rows are `name,buy` followed by the columns
+//!   the category needs.
+//! - `variation` is `none`, `charges` or `capacity` (with a column of the most charges or
+//!   capacity), `modifier` (with a seal slots column) or `stack`, as in `Variation`.
+//! - `states` lists the states found in order, separated by spaces: `normal`, `blessed` and
+//!   `cursed`.
+//! - `sell` is `sell` when the table has a sell price column after the buy price.
+
+use std::{
+	collections::HashMap,
+	env,
+	fmt::Write,
+	fs,
+	path::{Path, PathBuf},
+};
+
+// the highest price a table may list
+const MAX_PRICE: u32 = 1_000_000;
+// charges of a staff or capacity of a pot
+const MAX_COUNT: u32 = 10;
+const MAX_SLOTS: u32 = 9;
+
+/// A line of `categories.csv`.
+struct Category {
+	id: String,
+	name: String,
+	table: String,
+	/// the `Variation` it is turned into
+	variation: &'static str,
+	/// the `StateRule`s it is turned into
+	states: Vec<&'static str>,
+	sell: bool,
+	/// the most charges or capacity, counted down to 0
+	counts: bool,
+	slots: bool,
+}
+
+impl Category {
+	fn parse(line: &str) -> Result<Self, String> {
+		let values: Vec<_> = line.split(',').collect();
+		let [id, name, table, variation, states, sell] = values[..] else {
+			return Err(format!("expected 6 columns but found {}", values.len()));
+		};
+		for (column, value) in [("id", id), ("name", name), ("table", table)] {
+			if value.is_empty() || value.trim() != value {
+				return Err(format!("invalid {column} `{value}`"));
+			}
+		}
+		let variation = match variation {
+			"none" => "None",
+			"charges" => "Charges",
+			"capacity" => "Capacity",
+			"modifier" => "Modifier",
+			"stack" => "Stack",
+			_ => return Err(format!("unknown variation `{variation}`")),
+		};
+		let states = states
+			.split_whitespace()
+			.map(|state| match state {
+				"normal" => Ok("NORMAL"),
+				"blessed" => Ok("BLESSED"),
+				"cursed" => Ok("CURSED"),
+				_ => Err(format!("unknown state `{state}`")),
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+		if states.is_empty() {
+			return Err(String::from("no states"));
+		}
+		let sell = match sell {
+			"" => false,
+			"sell" => true,
+			_ => return Err(format!("expected `sell` or nothing but found `{sell}`")),
+		};
+
+		Ok(Self {
+			id: id.to_owned(),
+			name: name.to_owned(),
+			table: table.to_owned(),
+			variation,
+			states,
+			sell,
+			counts: matches!(variation, "Charges" | "Capacity"),
+			slots: variation == "Modifier",
+		})
+	}
+
+	fn get_columns(&self) -> usize {
+		2 + self.sell as usize + self.counts as usize + self.slots as usize
+	}
+}
+
+fn parse(value: &str, column: &str, min: u32, max: u32) -> Result<u32, String> {
+	let value: u32 = value
+		.parse()
+		.map_err(|_| format!("{column} `{value}` is not a number"))?;
+	if !(min..=max).contains(&value) {
+		return Err(format!("{column} {value} is not in {min}..={max}"));
+	}
+	Ok(value)
+}
+
+fn parse_row(table: &Category, line: &str) -> Result<String, String> {
+	let values: Vec<_> = line.split(',').collect();
+	if values.len() != table.get_columns() {
+		return Err(format!(
+			"expected {} columns but found {}",
+			table.get_columns(),
+			values.len()
+		));
+	}
+
+	let mut values = values.into_iter();
+	let name = values.next().unwrap();
+	if name.is_empty() || name.trim() != name {
+		return Err(format!("invalid name `{name}`"));
+	}
+	let buy = parse(values.next().unwrap(), "buy price", 1, MAX_PRICE)?;
+	let sell = if table.sell {
+		let sell = parse(values.next().unwrap(), "sell price", 0, buy)?;
+		format!("Some({sell})")
+	} else {
+		String::from("None")
+	};
+	let max_count = if table.counts {
+		let max = parse(values.next().unwrap(), "most count", 0, MAX_COUNT)?;
+		format!("Some({max})")
+	} else {
+		String::from("None")
+	};
+	let slots = if table.slots {
+		let slots = parse(values.next().unwrap(), "slots", 0, MAX_SLOTS)?;
+		format!("Some({slots})")
+	} else {
+		String::from("None")
+	};
+
+	Ok(format!(
+		"\t\t\tRow {{ name: {name:?}, buy: {buy}, sell: {sell}, max_count: {max_count}, slots: {slots} }},\n"
+	))
+}
+
+// the rows of `category`'s table, or the errors found in it
+fn parse_table(
+	dir: &Path,
+	category: &Category,
+	names: &mut HashMap<String, String>,
+	errors: &mut Vec<String>,
+) -> String {
+	let path = dir.join(&category.table);
+	println!("cargo::rerun-if-changed={}", path.display());
+	let csv = match fs::read_to_string(&path) {
+		Ok(csv) => csv,
+		Err(e) => {
+			errors.push(format!("{}: {e}", path.display()));
+			return String::new();
+		}
+	};
+
+	let mut rows = String::new();
+	for (i, line) in csv.lines().enumerate() {
+		let location = format!("{}:{}", path.display(), i + 1);
+		if line.trim().is_empty() {
+			continue;
+		}
+		match parse_row(category, line) {
+			Ok(row) => rows.push_str(&row),
+			Err(e) => errors.push(format!("{location}: {e}")),
+		}
+
+		let name = line.split(',').next().unwrap();
+		if let Some(first) = names.get(name) {
+			errors.push(format!("{location}: `{name}` is already listed at {first}"));
+		} else {
+			names.insert(name.to_owned(), location);
+		}
+	}
+	if rows.is_empty() {
+		errors.push(format!("{}: no items", path.display()));
+	}
+	rows
+}
+
+fn main() {
+	let dir = Path::new("src/price_list");
+	let manifest = dir.join("categories.csv");
+	println!("cargo::rerun-if-changed=build.rs");
+	println!("cargo::rerun-if-changed={}", manifest.display());
+
+	let mut code = String::new();
+	let mut len = 0;
+	// where each item name was first seen
+	let mut names = HashMap::new();
+	let mut ids = HashMap::new();
+	let mut errors = Vec::new();
+
+	let lines = fs::read_to_string(&manifest).unwrap_or_else(|e| {
+		errors.push(format!("{}: {e}", manifest.display()));
+		String::new()
+	});
+	for (i, line) in lines.lines().enumerate() {
+		let location = format!("{}:{}", manifest.display(), i + 1);
+		if line.trim().is_empty() {
+			continue;
+		}
+		let category = match Category::parse(line) {
+			Ok(category) => category,
+			Err(e) => {
+				errors.push(format!("{location}: {e}"));
+				continue;
+			}
+		};
+		if let Some(first) = ids.get(&category.id) {
+			errors.push(format!(
+				"{location}: `{}` is already listed at {first}",
+				category.id
+			));
+		} else {
+			ids.insert(category.id.clone(), location);
+		}
+
+		let rows = parse_table(dir, &category, &mut names, &mut errors);
+		let states: Vec<_> = category
+			.states
+			.iter()
+			.map(|state| format!("StateRule::{state}"))
+			.collect();
+		write!(
+			code,
+			"\tCategory {{\n\t\tid: {:?},\n\t\tname: {:?},\n\t\trows: &[\n{rows}\t\t],\n\t\t\
+			 rules: PriceRules {{\n\t\t\tstates: &[{}],\n\t\t\t..PriceRules::DEFAULT\n\t\t}},\n\t\t\
+			 variation: Variation::{},\n\t}},\n",
+			category.id,
+			category.name,
+			states.join(", "),
+			category.variation,
+		)
+		.unwrap();
+		len += 1;
+	}
+	if len == 0 {
+		errors.push(format!("{}: no categories", manifest.display()));
+	}
+
+	if !errors.is_empty() {
+		panic!("invalid item tables:\n{}", errors.join("\n"));
+	}
+
+	let code = format!("pub static CATEGORIES: [Category; {len}] = [\n{code}];\n");
+	let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("categories.rs");
+	fs::write(out, code).unwrap();
+}
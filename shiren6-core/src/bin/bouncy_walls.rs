@@ -0,0 +1,185 @@
+use std::{
+	env, fs,
+	io::{self, Read},
+	process::ExitCode,
+};
+
+use shiren6_core::bouncy_walls::{
+	cell::Cells,
+	export, json,
+	map::{Map, MapError},
+};
+
+const USAGE: &str = "\
+usage: bouncy_walls [options] [FILE]
+
+Reads a map in the textarea format from FILE, or from stdin when FILE is omitted or `-`,
+and prints the success paths, the bounce sides of every component and an ASCII overlay
+of each success path.
+
+options:
+  --json          print the map and its success paths as JSON instead
+  --expect N      exit with status 1 unless exactly N success paths are found
+  -h, --help      print this message";
+
+struct Options {
+	path: Option<String>,
+	json: bool,
+	expect: Option<usize>,
+}
+
+// `None` when the usage is asked for
+fn parse_args() -> Result<Option<Options>, String> {
+	let mut options = Options {
+		path: None,
+		json: false,
+		expect: None,
+	};
+
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-h" | "--help" => return Ok(None),
+			"--json" => options.json = true,
+			"--expect" => {
+				let n = args.next().ok_or("--expect needs a number")?;
+				options.expect = Some(n.parse().map_err(|_| format!("invalid number: {n}"))?);
+			}
+			"-" => options.path = None,
+			_ if arg.starts_with('-') => return Err(format!("unknown option: {arg}")),
+			_ if options.path.is_some() => return Err(String::from("too many files")),
+			_ => options.path = Some(arg),
+		}
+	}
+
+	Ok(Some(options))
+}
+
+fn read_input(path: Option<&str>) -> io::Result<String> {
+	match path {
+		Some(path) => fs::read_to_string(path),
+		None => {
+			let mut text = String::new();
+			io::stdin().read_to_string(&mut text)?;
+			Ok(text)
+		}
+	}
+}
+
+fn get_bounce_sides(bounce_flag: u32) -> String {
+	let sides: Vec<_> = [
+		(Cells::BOUNCE_TOP, "top"),
+		(Cells::BOUNCE_LEFT, "left"),
+		(Cells::BOUNCE_BOTTOM, "bottom"),
+		(Cells::BOUNCE_RIGHT, "right"),
+	]
+	.into_iter()
+	.filter(|&(flag, _)| bounce_flag & flag != 0)
+	.map(|(_, side)| side)
+	.collect();
+
+	if sides.is_empty() {
+		String::from("-")
+	} else {
+		sides.join(" ")
+	}
+}
+
+fn describe(cells: &Cells, v: usize) -> String {
+	let (_, width) = cells.get_size();
+	let layer = v / cells.len();
+	let i = v % cells.len();
+	format!("y={} x={} {}", i / width, i % width, ['\\', '/'][layer])
+}
+
+fn print_report(map: &Map) {
+	let cells = &map.cells;
+	let (height, width) = cells.get_size();
+	let components = cells.components();
+	let routes = cells.find_routes();
+
+	if let Some(name) = map.header.name.as_ref() {
+		println!("name: {name}");
+	}
+	if let Some(floor) = map.header.floor {
+		println!("floor: {floor}");
+	}
+	println!("size: {height}x{width}");
+	println!();
+
+	println!("components: {}", components.len());
+	for (i, &(v, bounce_flag)) in components.iter().enumerate() {
+		println!(
+			"  {:>3}  {:<14} bounces: {}{}",
+			i + 1,
+			describe(cells, v),
+			get_bounce_sides(bounce_flag),
+			if bounce_flag == Cells::BOUNCE_ALL {
+				"  (success)"
+			} else {
+				""
+			}
+		);
+	}
+	println!();
+
+	println!("success paths: {}", routes.len());
+	for (i, &v) in routes.iter().enumerate() {
+		println!();
+		println!("#{} {}", i + 1, describe(cells, v));
+		print!("{}", export::to_ascii(cells, &cells.trace(v)));
+	}
+}
+
+fn main() -> ExitCode {
+	let options = match parse_args() {
+		Ok(Some(options)) => options,
+		Ok(None) => {
+			println!("{USAGE}");
+			return ExitCode::SUCCESS;
+		}
+		Err(message) => {
+			eprintln!("error: {message}");
+			eprintln!("{USAGE}");
+			return ExitCode::from(2);
+		}
+	};
+
+	let text = match read_input(options.path.as_deref()) {
+		Ok(text) => text,
+		Err(e) => {
+			eprintln!("error: could not read the map: {e}");
+			return ExitCode::from(2);
+		}
+	};
+
+	let map = match Map::try_from(text.as_str()) {
+		Ok(map) => map,
+		Err(e) => {
+			let message = match e {
+				MapError::Cells(e) => format!("invalid map: {e:?}"),
+				MapError::DuplicateEntity => {
+					String::from("invalid map: more than one player or stairs")
+				}
+				e => format!("invalid header: {e:?}"),
+			};
+			eprintln!("error: {message}");
+			return ExitCode::from(2);
+		}
+	};
+
+	if options.json {
+		println!("{}", json::export(&map));
+	} else {
+		print_report(&map);
+	}
+
+	let found = map.cells.find_routes().len();
+	match options.expect {
+		Some(expected) if expected != found => {
+			eprintln!("expected {expected} success paths, found {found}");
+			ExitCode::FAILURE
+		}
+		_ => ExitCode::SUCCESS,
+	}
+}
@@ -0,0 +1,445 @@
+use std::{fmt, iter};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellKind {
+	Vacant,
+	Wall,
+	Pit,
+	Water,
+	BouncyWall,
+}
+
+impl CellKind {
+	pub fn get_bg_class_name(&self) -> &'static str {
+		use CellKind::*;
+
+		match self {
+			Vacant => "vacant_bg",
+			Wall => "wall_bg",
+			Pit => "pit_bg",
+			Water => "water_bg",
+			BouncyWall => "bouncy_wall_bg",
+		}
+	}
+
+	pub fn get_char(&self) -> char {
+		use CellKind::*;
+
+		match self {
+			Vacant => '.',
+			Wall => '#',
+			Pit => ',',
+			Water => '~',
+			BouncyWall => 'b',
+		}
+	}
+
+	pub fn is_solid(&self) -> bool {
+		use CellKind::*;
+
+		matches!(*self, Wall | BouncyWall)
+	}
+
+	/// The kind a character of the text format stands for.
+	pub fn from_char(c: char) -> Option<Self> {
+		use CellKind::*;
+
+		match c {
+			// path overlays from `export::to_ascii` are read back as vacant cells
+			' ' | '.' | '/' | '\\' => Some(Vacant),
+			'#' => Some(Wall),
+			',' | 'p' | 'P' => Some(Pit),
+			'~' | 'w' | 'W' => Some(Water),
+			'b' | 'B' => Some(BouncyWall),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cells {
+	kinds: Vec<CellKind>,
+	width: usize,
+	height: usize,
+	// Masks over the tiles surrounded by a one-tile border, `(y + 1) * (width + 2) + x + 1`.
+	// The border is outside, so a step from any tile can be looked up without bounds checks.
+	inside: BitSet,
+	solid: BitSet,
+	bouncy: BitSet,
+}
+
+impl Cells {
+	// 1 [#][#] 2 [#][/] 4 [\][/] 8 [\][#]
+	//   [/][\]   [#][\]   [#][#]   [/][#]
+	pub const BOUNCE_TOP: u32 = 1;
+	pub const BOUNCE_LEFT: u32 = 2;
+	pub const BOUNCE_BOTTOM: u32 = 4;
+	pub const BOUNCE_RIGHT: u32 = 8;
+	pub const BOUNCE_ALL: u32 =
+		Self::BOUNCE_TOP | Self::BOUNCE_LEFT | Self::BOUNCE_BOTTOM | Self::BOUNCE_RIGHT;
+
+	const MAX_SIZE: usize = 64;
+
+	pub fn from_kinds(
+		height: usize,
+		width: usize,
+		kinds: Vec<CellKind>,
+	) -> Result<Self, CellsError> {
+		if height > Self::MAX_SIZE || width > Self::MAX_SIZE {
+			return Err(CellsError::TooLarge);
+		}
+		if kinds.len() != height * width {
+			return Err(CellsError::SizeMismatch);
+		}
+
+		Ok(Self::new(height, width, kinds))
+	}
+
+	fn new(height: usize, width: usize, kinds: Vec<CellKind>) -> Self {
+		let stride = width + 2;
+		let mut inside = BitSet::new((height + 2) * stride);
+		let mut solid = inside.clone();
+		let mut bouncy = inside.clone();
+
+		for (i, kind) in kinds.iter().enumerate() {
+			let p = (i / width + 1) * stride + i % width + 1;
+			inside.insert(p);
+			if kind.is_solid() {
+				solid.insert(p);
+			}
+			if *kind == CellKind::BouncyWall {
+				bouncy.insert(p);
+			}
+		}
+
+		Self {
+			kinds,
+			width,
+			height,
+			inside,
+			solid,
+			bouncy,
+		}
+	}
+
+	pub fn get(&self, y: usize, x: usize) -> Option<CellKind> {
+		(y < self.height && x < self.width).then(|| self.kinds[y * self.width + x])
+	}
+
+	pub fn get_size(&self) -> (usize, usize) {
+		(self.height, self.width)
+	}
+
+	pub fn len(&self) -> usize {
+		self.kinds.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.kinds.is_empty()
+	}
+
+	pub fn kinds(&self) -> &[CellKind] {
+		&self.kinds
+	}
+
+	pub fn set(&mut self, y: usize, x: usize, kind: CellKind) {
+		let mut kinds = std::mem::take(&mut self.kinds);
+		kinds[y * self.width + x] = kind;
+		*self = Self::new(self.height, self.width, kinds);
+	}
+
+	/// Inserts a row of vacant cells above row `y`.
+	pub fn insert_row(&mut self, y: usize) -> Result<(), CellsError> {
+		if self.height == Self::MAX_SIZE {
+			return Err(CellsError::TooLarge);
+		}
+		let mut kinds = std::mem::take(&mut self.kinds);
+		let i = y * self.width;
+		kinds.splice(i..i, iter::repeat_n(CellKind::Vacant, self.width));
+		*self = Self::new(self.height + 1, self.width, kinds);
+		Ok(())
+	}
+
+	/// Removes row `y` unless it is the only one.
+	pub fn remove_row(&mut self, y: usize) {
+		if self.height <= 1 {
+			return;
+		}
+		let mut kinds = std::mem::take(&mut self.kinds);
+		let i = y * self.width;
+		kinds.drain(i..i + self.width);
+		*self = Self::new(self.height - 1, self.width, kinds);
+	}
+
+	/// Inserts a column of vacant cells left of column `x`.
+	pub fn insert_column(&mut self, x: usize) -> Result<(), CellsError> {
+		if self.width == Self::MAX_SIZE {
+			return Err(CellsError::TooLarge);
+		}
+		let kinds = self
+			.kinds
+			.chunks(self.width.max(1))
+			.flat_map(|row| {
+				let (left, right) = row.split_at(x);
+				left.iter()
+					.chain(iter::once(&CellKind::Vacant))
+					.chain(right)
+					.copied()
+			})
+			.collect();
+		*self = Self::new(self.height, self.width + 1, kinds);
+		Ok(())
+	}
+
+	/// Removes column `x` unless it is the only one.
+	pub fn remove_column(&mut self, x: usize) {
+		if self.width <= 1 {
+			return;
+		}
+		let kinds = self
+			.kinds
+			.chunks(self.width)
+			.flat_map(|row| row[..x].iter().chain(&row[x + 1..]).copied())
+			.collect();
+		*self = Self::new(self.height, self.width - 1, kinds);
+	}
+
+	/// The `(y, x)` of every tile that differs from `other`, or `None` when the sizes differ.
+	pub fn get_changes(&self, other: &Self) -> Option<Vec<(usize, usize)>> {
+		(self.get_size() == other.get_size()).then(|| {
+			(0..self.len())
+				.filter(|&i| self.kinds[i] != other.kinds[i])
+				.map(|i| (i / self.width, i % self.width))
+				.collect()
+		})
+	}
+
+	pub fn find_routes(&self) -> Vec<usize> {
+		self.components()
+			.into_iter()
+			.filter(|&(_, bounce_flag)| bounce_flag == Self::BOUNCE_ALL)
+			.map(|(v, _)| v)
+			.collect()
+	}
+
+	/// Every connected component as its representative and the sides of bouncy walls it
+	/// bounces off.
+	pub fn components(&self) -> Vec<(usize, u32)> {
+		let mut components = Vec::new();
+
+		let mut visited = BitSet::new(self.len() * 2);
+
+		for (i, _) in self
+			.kinds
+			.iter()
+			.enumerate()
+			.filter(|(_, kind)| !kind.is_solid())
+		{
+			for v in [i, i + self.len()].into_iter() {
+				if visited.get(v) {
+					continue;
+				}
+
+				let bounce_flag = self.dfs(v, &mut visited, |_| {});
+				components.push((v, bounce_flag));
+			}
+		}
+
+		components
+	}
+
+	pub fn trace(&self, v: usize) -> Vec<u8> {
+		let mut trace = vec![0; self.len()];
+
+		let mut visited = BitSet::new(self.len() * 2);
+
+		self.dfs(v, &mut visited, |v| {
+			let layer = v / self.len();
+			trace[v % self.len()] |= 1 << layer;
+		});
+
+		trace
+	}
+
+	pub(super) fn dfs<F>(&self, v: usize, visited: &mut BitSet, mut f: F) -> u32
+	where
+		F: FnMut(usize),
+	{
+		let len = self.len();
+		let stride = self.width + 2;
+		let pad = |v: usize| {
+			let i = v % len;
+			(i / self.width + 1) * stride + i % self.width + 1
+		};
+
+		let mut bounce_flag = 0;
+
+		// each vertex is paired with its tile in the masks
+		let mut stack = vec![(v, pad(v))];
+		visited.insert(v);
+
+		while let Some((v, p)) = stack.pop() {
+			f(v);
+
+			let (dy, flipped) = if v < len {
+				([-1, 1], v + len)
+			} else {
+				([1, -1], v - len)
+			};
+
+			for (dy, dx) in dy.into_iter().zip([-1, 1]) {
+				let target = p.wrapping_add_signed(dy * stride as isize + dx);
+				if !self.inside.get(target) {
+					continue;
+				}
+				let adj_y = p.wrapping_add_signed(dy * stride as isize);
+				let adj_x = p.wrapping_add_signed(dx);
+
+				let (nv, np) = if !self.solid.get(target) {
+					// go straight
+					(v.wrapping_add_signed(dy * self.width as isize + dx), target)
+				} else {
+					match (self.solid.get(adj_y), self.solid.get(adj_x)) {
+						(true, false) => {
+							if self.bouncy.get(target) && self.bouncy.get(adj_y) {
+								bounce_flag |= if dy == -1 {
+									Self::BOUNCE_TOP
+								} else {
+									Self::BOUNCE_BOTTOM
+								};
+							}
+							(flipped.wrapping_add_signed(dx), adj_x)
+						}
+						(false, true) => {
+							if self.bouncy.get(target) && self.bouncy.get(adj_x) {
+								bounce_flag |= if dx == -1 {
+									Self::BOUNCE_LEFT
+								} else {
+									Self::BOUNCE_RIGHT
+								};
+							}
+							(flipped.wrapping_add_signed(dy * self.width as isize), adj_y)
+						}
+						_ => continue,
+					}
+				};
+				if visited.insert(nv) {
+					stack.push((nv, np));
+				}
+			}
+		}
+
+		bounce_flag
+	}
+}
+
+/// Bits packed into words, used for the tile masks and visited vertices.
+#[derive(Clone, PartialEq, Eq)]
+pub(super) struct BitSet(Vec<u64>);
+
+impl BitSet {
+	pub(super) fn new(len: usize) -> Self {
+		Self(vec![0; len.div_ceil(64)])
+	}
+
+	pub(super) fn get(&self, i: usize) -> bool {
+		self.0[i >> 6] >> (i & 63) & 1 != 0
+	}
+
+	/// Sets the bit and returns whether it was unset.
+	pub(super) fn insert(&mut self, i: usize) -> bool {
+		let word = &mut self.0[i >> 6];
+		let bit = 1 << (i & 63);
+		let unset = *word & bit == 0;
+		*word |= bit;
+		unset
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellsError {
+	InvalidChar,
+	TooLarge,
+	SizeMismatch,
+}
+
+impl TryFrom<&str> for Cells {
+	type Error = CellsError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let value = value.trim_end().trim_start_matches("\n");
+		let (height, width) = value
+			.lines()
+			.enumerate()
+			.map(|(i, line)| (i, line.chars().count()))
+			.fold((0, 0), |r, (i, c)| (i + 1, r.1.max(c)));
+
+		if height > Self::MAX_SIZE || width > Self::MAX_SIZE {
+			return Err(CellsError::TooLarge);
+		}
+
+		let mut kinds = Vec::with_capacity(height * width);
+
+		for line in value.lines() {
+			for c in line.chars().chain(iter::repeat_with(|| '.')).take(width) {
+				let kind = CellKind::from_char(c).ok_or(CellsError::InvalidChar)?;
+				kinds.push(kind);
+			}
+		}
+
+		Ok(Self::new(height, width, kinds))
+	}
+}
+
+impl fmt::Display for Cells {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.width == 0 {
+			return Ok(());
+		}
+		for row in self.kinds.chunks(self.width) {
+			for kind in row {
+				write!(f, "{}", kind.get_char())?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::bouncy_walls::reference::{self, Reference};
+
+	fn to_reference(cells: &Cells) -> Reference {
+		let (height, width) = cells.get_size();
+		let solid = cells.kinds().iter().map(|kind| kind.is_solid()).collect();
+		let bouncy = cells
+			.kinds()
+			.iter()
+			.map(|&kind| kind == CellKind::BouncyWall)
+			.collect();
+		Reference::new(height, width, solid, bouncy)
+	}
+
+	#[test]
+	fn masks_match_reference() {
+		let mut seed = 0x9e37_79b9_7f4a_7c15;
+		for _ in 0..3000 {
+			let height = reference::random(&mut seed, 12) + 1;
+			let width = reference::random(&mut seed, 12) + 1;
+			let map = reference::random_map(&mut seed, height, width);
+			let cells = Cells::try_from(map.as_str()).unwrap();
+			let expected = to_reference(&cells);
+
+			assert_eq!(cells.components(), expected.components(), "{map}");
+			assert_eq!(cells.find_routes(), expected.find_routes(), "{map}");
+			for (v, _) in cells.components() {
+				assert_eq!(cells.trace(v), expected.trace(v), "{map}");
+			}
+		}
+	}
+}
@@ -0,0 +1,185 @@
+use super::cell::{BitSet, Cells};
+
+#[derive(Clone)]
+struct Component {
+	representative: usize,
+	bounce_flag: u32,
+	vertices: Vec<usize>,
+}
+
+/// The connected components of a map kept up to date across small edits.
+///
+/// Whether a vertex goes straight or bounces depends only on the 3x3 tiles around it, so
+/// editing a tile can only change the components that pass through the tile or one of its
+/// neighbours. `update` recomputes those and keeps the rest.
+#[derive(Clone)]
+pub struct Components {
+	// component index of each vertex, `None` on solid tiles
+	labels: Vec<Option<usize>>,
+	// slots freed by `update` are `None` until reused
+	components: Vec<Option<Component>>,
+	free: Vec<usize>,
+}
+
+impl Components {
+	pub fn new(cells: &Cells) -> Self {
+		let mut components = Self {
+			labels: vec![None; cells.len() * 2],
+			components: Vec::new(),
+			free: Vec::new(),
+		};
+		let seeds = (0..cells.len() * 2).collect();
+		components.search(cells, seeds);
+
+		components
+	}
+
+	/// Re-analyses after the tiles at `edits`, given as `(y, x)`, were changed in `cells`.
+	/// Falls back to a full analysis when the size changed.
+	pub fn update(&mut self, cells: &Cells, edits: &[(usize, usize)]) {
+		if self.labels.len() != cells.len() * 2 {
+			*self = Self::new(cells);
+			return;
+		}
+
+		let (height, width) = cells.get_size();
+		let mut seeds = Vec::new();
+
+		for &(y, x) in edits {
+			for ny in y.saturating_sub(1)..(y + 2).min(height) {
+				for nx in x.saturating_sub(1)..(x + 2).min(width) {
+					let i = ny * width + nx;
+					seeds.extend([i, i + cells.len()]);
+				}
+			}
+		}
+
+		for i in 0..seeds.len() {
+			let Some(index) = self.labels[seeds[i]] else {
+				continue;
+			};
+			let component = self.components[index].take().unwrap();
+			for &v in &component.vertices {
+				self.labels[v] = None;
+			}
+			seeds.extend(component.vertices);
+			self.free.push(index);
+		}
+
+		self.search(cells, seeds);
+	}
+
+	/// Same as `Cells::find_routes`.
+	pub fn find_routes(&self) -> Vec<usize> {
+		self.get_components()
+			.into_iter()
+			.filter(|&(_, bounce_flag)| bounce_flag == Cells::BOUNCE_ALL)
+			.map(|(v, _)| v)
+			.collect()
+	}
+
+	/// Same as `Cells::components`, in the same order.
+	pub fn get_components(&self) -> Vec<(usize, u32)> {
+		let len = self.labels.len() / 2;
+		let mut components: Vec<_> = self
+			.components
+			.iter()
+			.flatten()
+			.map(|component| (component.representative, component.bounce_flag))
+			.collect();
+		components.sort_by_key(|&(v, _)| (v % len, v / len));
+
+		components
+	}
+
+	/// The representative of the component containing `v`.
+	pub fn get_representative(&self, v: usize) -> Option<usize> {
+		self.get_component(v)
+			.map(|component| component.representative)
+	}
+
+	/// The vertices of the component containing `v`, in no particular order.
+	pub fn get_vertices(&self, v: usize) -> Option<&[usize]> {
+		self.get_component(v)
+			.map(|component| component.vertices.as_slice())
+	}
+
+	fn get_component(&self, v: usize) -> Option<&Component> {
+		let index = self.labels.get(v).copied().flatten()?;
+		self.components[index].as_ref()
+	}
+
+	// Searches from every unlabelled vertex of `seeds`, in the order `Cells::components` visits
+	// them so that each representative is the one a full analysis would pick.
+	fn search(&mut self, cells: &Cells, mut seeds: Vec<usize>) {
+		let len = cells.len();
+		seeds.retain(|&v| !cells.kinds()[v % len].is_solid());
+		seeds.sort_unstable_by_key(|&v| (v % len, v / len));
+		seeds.dedup();
+
+		let mut visited = BitSet::new(self.labels.len());
+		for (v, label) in self.labels.iter().enumerate() {
+			if label.is_some() {
+				visited.insert(v);
+			}
+		}
+
+		for v in seeds {
+			if visited.get(v) {
+				continue;
+			}
+
+			let mut vertices = Vec::new();
+			let bounce_flag = cells.dfs(v, &mut visited, |v| vertices.push(v));
+
+			let index = self.free.pop().unwrap_or(self.components.len());
+			for &v in &vertices {
+				self.labels[v] = Some(index);
+			}
+			let component = Some(Component {
+				representative: v,
+				bounce_flag,
+				vertices,
+			});
+			if index == self.components.len() {
+				self.components.push(component);
+			} else {
+				self.components[index] = component;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::bouncy_walls::{cell::CellKind, reference};
+
+	#[test]
+	fn update_matches_full_analysis() {
+		let mut seed = 0x853c_49e6_748f_ea9b;
+		for _ in 0..3000 {
+			let height = reference::random(&mut seed, 12) + 1;
+			let width = reference::random(&mut seed, 12) + 1;
+			let map = reference::random_map(&mut seed, height, width);
+			let mut cells = Cells::try_from(map.as_str()).unwrap();
+			let mut components = Components::new(&cells);
+
+			for _ in 0..4 {
+				let edits: Vec<_> = (0..reference::random(&mut seed, 3) + 1)
+					.map(|_| {
+						let y = reference::random(&mut seed, height);
+						let x = reference::random(&mut seed, width);
+						let kind = CellKind::from_char(reference::random_tile(&mut seed));
+						cells.set(y, x, kind.unwrap());
+						(y, x)
+					})
+					.collect();
+				components.update(&cells, &edits);
+
+				assert_eq!(components.get_components(), cells.components(), "{cells}");
+				assert_eq!(components.find_routes(), cells.find_routes(), "{cells}");
+			}
+		}
+	}
+}
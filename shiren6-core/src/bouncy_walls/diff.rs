@@ -0,0 +1,120 @@
+use super::{cell::Cells, components::Components};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteChangeKind {
+	Unchanged,
+	/// still one path, but running through different tiles
+	Changed,
+	Appeared,
+	Disappeared,
+	Merged,
+	Split,
+}
+
+/// Success paths of the two versions that share a vertex, directly or through each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteChange {
+	/// representatives in the map before the change
+	pub before: Vec<usize>,
+	/// representatives in the map after the change
+	pub after: Vec<usize>,
+	pub kind: RouteChangeKind,
+}
+
+pub struct CellsDiff {
+	/// `(y, x)` of the tiles whose kind changed
+	pub changes: Vec<(usize, usize)>,
+	pub routes: Vec<RouteChange>,
+}
+
+/// Compares two maps of the same size, or returns `None` when the sizes differ.
+pub fn diff(before: &Cells, after: &Cells) -> Option<CellsDiff> {
+	let changes = before.get_changes(after)?;
+
+	let before_components = Components::new(before);
+	let mut after_components = before_components.clone();
+	after_components.update(after, &changes);
+
+	let before_routes = before_components.find_routes();
+	let after_routes = after_components.find_routes();
+
+	// union-find over the paths, before ones first
+	let mut parents: Vec<_> = (0..before_routes.len() + after_routes.len()).collect();
+	fn find(parents: &mut [usize], mut i: usize) -> usize {
+		while parents[i] != i {
+			parents[i] = parents[parents[i]];
+			i = parents[i];
+		}
+		i
+	}
+
+	for (i, &v) in before_routes.iter().enumerate() {
+		for &u in before_components.get_vertices(v).unwrap() {
+			let Some(r) = after_components.get_representative(u) else {
+				continue;
+			};
+			if let Ok(j) = after_routes.binary_search_by_key(&key(after, r), |&v| key(after, v)) {
+				let (a, b) = (
+					find(&mut parents, i),
+					find(&mut parents, before_routes.len() + j),
+				);
+				parents[a] = b;
+			}
+		}
+	}
+
+	let mut groups: Vec<(usize, RouteChange)> = Vec::new();
+	for i in 0..parents.len() {
+		let root = find(&mut parents, i);
+		let index = match groups.iter().position(|&(r, _)| r == root) {
+			Some(index) => index,
+			None => {
+				groups.push((
+					root,
+					RouteChange {
+						before: Vec::new(),
+						after: Vec::new(),
+						kind: RouteChangeKind::Unchanged,
+					},
+				));
+				groups.len() - 1
+			}
+		};
+		let change = &mut groups[index].1;
+		match i.checked_sub(before_routes.len()) {
+			None => change.before.push(before_routes[i]),
+			Some(j) => change.after.push(after_routes[j]),
+		}
+	}
+
+	let routes = groups
+		.into_iter()
+		.map(|(_, mut change)| {
+			change.kind = match (change.before.as_slice(), change.after.as_slice()) {
+				([], _) => RouteChangeKind::Appeared,
+				(_, []) => RouteChangeKind::Disappeared,
+				(&[b], &[a]) => {
+					let mut before_vertices = before_components.get_vertices(b).unwrap().to_vec();
+					let mut after_vertices = after_components.get_vertices(a).unwrap().to_vec();
+					before_vertices.sort_unstable();
+					after_vertices.sort_unstable();
+					if before_vertices == after_vertices {
+						RouteChangeKind::Unchanged
+					} else {
+						RouteChangeKind::Changed
+					}
+				}
+				([_], _) => RouteChangeKind::Split,
+				_ => RouteChangeKind::Merged,
+			};
+			change
+		})
+		.collect();
+
+	Some(CellsDiff { changes, routes })
+}
+
+// the order `find_routes` returns representatives in
+fn key(cells: &Cells, v: usize) -> (usize, usize) {
+	(v % cells.len(), v / cells.len())
+}
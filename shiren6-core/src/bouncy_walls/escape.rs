@@ -0,0 +1,347 @@
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use super::{
+	map::{EntityKind, Map},
+	walk::WalkRules,
+};
+
+// gives up rather than freezing the page on a large floor full of monsters
+const MAX_STATES: usize = 30_000;
+// how many turns longer than the walk without monsters a plan may take, as dodging for
+// longer grows the search past any use
+pub const MAX_DETOUR: usize = 6;
+// a state is packed into a `u128`, 16 bits for the player and for each monster
+const MAX_MONSTERS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Monster {
+	pub y: usize,
+	pub x: usize,
+	pub fast: bool,
+}
+
+/// Positions at the end of a turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+	pub player: (usize, usize),
+	pub monsters: Vec<Monster>,
+	/// whether a monster stands next to the player
+	pub adjacent: bool,
+}
+
+/// The turn-by-turn simulation of the best escape, starting with the initial positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+	pub turns: Vec<Turn>,
+	pub adjacent_turns: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeError {
+	NoPlayer,
+	NoStairs,
+	Unreachable,
+	/// every way found takes more than `MAX_DETOUR` turns longer than the shortest walk
+	DetourTooLong,
+	TooComplex,
+}
+
+/// Finds a way from the player to the stairs that ends as few turns as possible next to a
+/// monster, and among those the shortest. Plans at most `MAX_DETOUR` turns longer than the
+/// shortest walk are considered, so the plan is the best within that.
+///
+/// Each turn the player steps or waits, then every monster chases: it steps to the tile
+/// closest to the player and attacks instead when already adjacent. Fast monsters do this
+/// twice. Monsters walk on vacant cells only, and the player by `rules`. Reaching the stairs
+/// ends the floor before the monsters move.
+pub fn plan_escape(map: &Map, rules: WalkRules) -> Result<Plan, EscapeError> {
+	let find = |kind| {
+		map.entities
+			.iter()
+			.find(|entity| entity.kind == kind)
+			.map(|entity| (entity.y, entity.x))
+	};
+	let player = find(EntityKind::Player).ok_or(EscapeError::NoPlayer)?;
+	let stairs = find(EntityKind::Stairs).ok_or(EscapeError::NoStairs)?;
+	let monsters: Vec<_> = map
+		.entities
+		.iter()
+		.filter_map(|entity| match entity.kind {
+			EntityKind::Monster => Some(Monster {
+				y: entity.y,
+				x: entity.x,
+				fast: false,
+			}),
+			EntityKind::FastMonster => Some(Monster {
+				y: entity.y,
+				x: entity.x,
+				fast: true,
+			}),
+			_ => None,
+		})
+		.collect();
+
+	if monsters.len() > MAX_MONSTERS || map.cells.len() > 1 << 16 {
+		return Err(EscapeError::TooComplex);
+	}
+
+	let start = Turn {
+		adjacent: is_adjacent(map, player, &monsters),
+		player,
+		monsters,
+	};
+	if player == stairs {
+		return Ok(Plan {
+			turns: vec![start],
+			adjacent_turns: 0,
+		});
+	}
+
+	// monsters only ever stand in the way, so the walk alone bounds the turns left
+	let distances = get_distances(map, rules, stairs);
+	let (_, width) = map.cells.get_size();
+	let index = |(y, x): (usize, usize)| y * width + x;
+	if distances[index(player)].is_none() {
+		return Err(EscapeError::Unreachable);
+	}
+	let limit = distances[index(player)].unwrap() + MAX_DETOUR;
+	let monster_steps = get_monster_steps(map);
+	// whether a longer way was left out, so that not finding one says nothing
+	let mut cut = false;
+	let pack = |player: (usize, usize), monsters: &[Monster]| {
+		monsters
+			.iter()
+			.rev()
+			.fold(0, |key, m| key << 16 | index((m.y, m.x)) as u128)
+			<< 16 | index(player) as u128
+	};
+
+	// A* over (player, monsters), ordered by (adjacent turns, turns)
+	let mut turns = vec![start];
+	let mut previous: Vec<Option<usize>> = vec![None];
+	let mut best = HashMap::from([(pack(player, &turns[0].monsters), (0, 0))]);
+	let mut heap = BinaryHeap::from([Reverse((0, 0, 0, 0))]);
+
+	while let Some(Reverse((adjacent_turns, _, length, index))) = heap.pop() {
+		let turn = &turns[index];
+		if best[&pack(turn.player, &turn.monsters)] < (adjacent_turns, length) {
+			continue;
+		}
+		if turn.player == stairs {
+			let mut plan = vec![turns[index].clone()];
+			let mut index = index;
+			while let Some(i) = previous[index] {
+				plan.push(turns[i].clone());
+				index = i;
+			}
+			plan.reverse();
+			return Ok(Plan {
+				turns: plan,
+				adjacent_turns,
+			});
+		}
+		if turns.len() > MAX_STATES {
+			return Err(EscapeError::TooComplex);
+		}
+
+		let (y, x) = turn.player;
+		let moves = rules
+			.get_steps(&map.cells, y, x)
+			.map(|(_, next)| next)
+			.chain([(y, x)])
+			.filter_map(|next| Some((next, distances[next.0 * width + next.1]?)))
+			.filter(|&(next, _)| !turn.monsters.iter().any(|m| (m.y, m.x) == next))
+			.collect::<Vec<_>>();
+
+		for (next, distance) in moves {
+			if length + 1 + distance > limit {
+				cut = true;
+				continue;
+			}
+			let monsters = if next == stairs {
+				turns[index].monsters.clone()
+			} else {
+				chase(map, &monster_steps, next, &turns[index].monsters)
+			};
+			let adjacent = next != stairs && is_adjacent(map, next, &monsters);
+			let cost = (adjacent_turns + adjacent as usize, length + 1);
+
+			let key = pack(next, &monsters);
+			if best.get(&key).is_some_and(|&best| best <= cost) {
+				continue;
+			}
+			best.insert(key, cost);
+			turns.push(Turn {
+				player: next,
+				monsters,
+				adjacent,
+			});
+			previous.push(Some(index));
+			heap.push(Reverse((
+				cost.0,
+				cost.1 + distance,
+				cost.1,
+				turns.len() - 1,
+			)));
+		}
+	}
+
+	Err(if cut {
+		EscapeError::DetourTooLong
+	} else {
+		EscapeError::Unreachable
+	})
+}
+
+// the fewest steps from each tile to `to`, ignoring monsters
+fn get_distances(map: &Map, rules: WalkRules, to: (usize, usize)) -> Vec<Option<usize>> {
+	let (_, width) = map.cells.get_size();
+	let mut distances = vec![None; map.cells.len()];
+	let mut queue = VecDeque::from([to]);
+	distances[to.0 * width + to.1] = Some(0);
+
+	// steps are blocked the same both ways, so walking out from `to` finds them all
+	while let Some((y, x)) = queue.pop_front() {
+		let distance = distances[y * width + x].unwrap();
+		for (_, (ny, nx)) in rules.get_steps(&map.cells, y, x) {
+			if distances[ny * width + nx].is_none() {
+				distances[ny * width + nx] = Some(distance + 1);
+				queue.push_back((ny, nx));
+			}
+		}
+	}
+
+	distances
+}
+
+// the tiles a monster can step to from each tile
+fn get_monster_steps(map: &Map) -> Vec<Vec<(usize, usize)>> {
+	let (_, width) = map.cells.get_size();
+	let rules = WalkRules::default();
+	(0..map.cells.len())
+		.map(|i| {
+			rules
+				.get_steps(&map.cells, i / width, i % width)
+				.map(|(_, next)| next)
+				.collect()
+		})
+		.collect()
+}
+
+fn distance((y, x): (usize, usize), (ty, tx): (usize, usize)) -> (usize, usize) {
+	let (dy, dx) = (y.abs_diff(ty), x.abs_diff(tx));
+	// Chebyshev first, then the straighter approach
+	(dy.max(dx), dy * dy + dx * dx)
+}
+
+/// Whether a monster at `(y, x)` can attack `(ty, tx)`, which like a diagonal step it cannot
+/// across the corner of a wall.
+fn can_attack(map: &Map, (y, x): (usize, usize), (ty, tx): (usize, usize)) -> bool {
+	let solid = |y, x| map.cells.get(y, x).unwrap().is_solid();
+	distance((y, x), (ty, tx)).0 == 1 && !solid(y, tx) && !solid(ty, x)
+}
+
+fn is_adjacent(map: &Map, player: (usize, usize), monsters: &[Monster]) -> bool {
+	monsters.iter().any(|m| can_attack(map, (m.y, m.x), player))
+}
+
+fn chase(
+	map: &Map,
+	monster_steps: &[Vec<(usize, usize)>],
+	player: (usize, usize),
+	monsters: &[Monster],
+) -> Vec<Monster> {
+	let (_, width) = map.cells.get_size();
+	let mut monsters = monsters.to_vec();
+
+	for i in 0..monsters.len() {
+		let steps = if monsters[i].fast { 2 } else { 1 };
+		for _ in 0..steps {
+			let Monster { y, x, .. } = monsters[i];
+			if can_attack(map, (y, x), player) {
+				break;
+			}
+			let next = monster_steps[y * width + x]
+				.iter()
+				.copied()
+				.filter(|&next| next != player && !monsters.iter().any(|m| (m.y, m.x) == next))
+				.min_by_key(|&next| distance(next, player))
+				.filter(|&next| distance(next, player) < distance((y, x), player));
+			match next {
+				Some((ny, nx)) => (monsters[i].y, monsters[i].x) = (ny, nx),
+				None => break,
+			}
+		}
+	}
+
+	monsters
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn no_attack_across_a_corner() {
+		let map = Map::try_from("@#\n.m").unwrap();
+		let monster = Monster {
+			y: 1,
+			x: 1,
+			fast: false,
+		};
+
+		assert!(!is_adjacent(&map, (0, 0), &[monster]));
+		// steps around the corner rather than attacking through it
+		let monsters = chase(&map, &get_monster_steps(&map), (0, 0), &[monster]);
+		assert_eq!((monsters[0].y, monsters[0].x), (1, 0));
+		assert!(is_adjacent(&map, (0, 0), &monsters));
+	}
+
+	#[test]
+	fn monster_follows_to_the_stairs() {
+		// the monster keeps up with every step but the last, which reaches the stairs
+		let map = Map::try_from("@...\nm..>").unwrap();
+		let plan = plan_escape(&map, WalkRules::default()).unwrap();
+
+		assert_eq!(plan.turns.len() - 1, 3);
+		assert_eq!(plan.adjacent_turns, 2);
+		assert!(plan.turns[0].adjacent);
+		assert!(!plan.turns[3].adjacent);
+	}
+
+	#[test]
+	fn long_detour_is_reported() {
+		// the monster cannot leave the water around it, leaving only the long way round
+		let map = Map::try_from("@~m~>\n#~#~#\n#~#~#\n#~#~#\n#~#~#\n#~~~#").unwrap();
+		let rules = WalkRules {
+			through_water: true,
+			through_pits: false,
+		};
+
+		assert_eq!(plan_escape(&map, rules), Err(EscapeError::DetourTooLong));
+	}
+
+	#[test]
+	fn open_room_is_planned() {
+		let mut rows = Vec::new();
+		for y in 0..20 {
+			let row: String = (0..20)
+				.map(|x| match (y, x) {
+					(0, 0) => '@',
+					(19, 19) => '>',
+					(5, 12) | (14, 3) => 'm',
+					(10, 10) | (3, 17) => 'M',
+					_ => '.',
+				})
+				.collect();
+			rows.push(row);
+		}
+		let map = Map::try_from(rows.join("\n").as_str()).unwrap();
+
+		let plan = plan_escape(&map, WalkRules::default()).unwrap();
+		assert_eq!(plan.turns.last().unwrap().player, (19, 19));
+	}
+}
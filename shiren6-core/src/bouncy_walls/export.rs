@@ -0,0 +1,608 @@
+use std::fmt::Write;
+
+use super::cell::{CellKind, Cells};
+
+// same geometry as `.view` in style.css
+const PADDING: usize = 15;
+const CELL_SIZE: usize = 64;
+const GAP: usize = 6;
+const PITCH: usize = CELL_SIZE + GAP;
+// `.path` overflows the cell by 5px on each side so that the lines connect through the gaps
+const PATH_MARGIN: usize = 5;
+const PATH_WIDTH: usize = 6;
+const CORNER_RADIUS: usize = 5;
+
+const BACKGROUND: [u8; 3] = [176, 176, 176];
+const PATH_COLOR: [u8; 3] = [255, 0, 0];
+const BOUNCY_COLORS: [[u8; 3]; 2] = [[248, 0, 180], [24, 24, 248]];
+const MARKER_COLOR: [u8; 3] = [255, 255, 255];
+const MARKER_BORDER: [u8; 3] = [32, 32, 32];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	UpLeft,
+	UpRight,
+	DownLeft,
+	DownRight,
+}
+
+impl Direction {
+	pub fn get_arrow(&self) -> &'static str {
+		use Direction::*;
+
+		match self {
+			UpLeft => "↖",
+			UpRight => "↗",
+			DownLeft => "↙",
+			DownRight => "↘",
+		}
+	}
+
+	fn get_delta(&self) -> (isize, isize) {
+		use Direction::*;
+
+		match self {
+			UpLeft => (-1, -1),
+			UpRight => (-1, 1),
+			DownLeft => (1, -1),
+			DownRight => (1, 1),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Marker {
+	pub y: usize,
+	pub x: usize,
+	pub direction: Option<Direction>,
+}
+
+fn get_fill(kind: CellKind) -> [u8; 3] {
+	use CellKind::*;
+
+	match kind {
+		Vacant => [208, 144, 0],
+		Wall => [96, 64, 0],
+		Pit => [32, 32, 0],
+		Water => [0, 96, 200],
+		BouncyWall => BOUNCY_COLORS[0],
+	}
+}
+
+fn get_image_size(cells: &Cells) -> (usize, usize) {
+	let (height, width) = cells.get_size();
+	let length = |n: usize| PADDING * 2 + (n * PITCH).saturating_sub(GAP);
+	(length(height), length(width))
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> String {
+	format!("rgb({r},{g},{b})")
+}
+
+/// Writes the map in the textarea format with the traced path drawn over the vacant cells
+/// using `\` and `/`. Pits and water keep their own symbols so that the text can be pasted
+/// back into the textarea as the same map.
+pub fn to_ascii(cells: &Cells, trace: &[u8]) -> String {
+	let (height, width) = cells.get_size();
+	let mut ascii = String::with_capacity(height * (width + 1));
+
+	for y in 0..height {
+		for x in 0..width {
+			let kind = cells.get(y, x).unwrap();
+			ascii.push(match (kind, trace[y * width + x]) {
+				(CellKind::Vacant, 1) => '\\',
+				(CellKind::Vacant, 2) => '/',
+				(kind, _) => kind.get_char(),
+			});
+		}
+		ascii.push('\n');
+	}
+
+	ascii
+}
+
+/// Renders the cells, the traced path and the marker as a standalone SVG document.
+pub fn to_svg(cells: &Cells, trace: Option<&[u8]>, marker: Option<Marker>) -> String {
+	let (height, width) = cells.get_size();
+	let (image_height, image_width) = get_image_size(cells);
+
+	let mut svg = String::new();
+	// writing into a `String` never fails
+	let _ = writeln!(
+		svg,
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{image_width}" height="{image_height}" viewBox="0 0 {image_width} {image_height}">"#
+	);
+	let _ = writeln!(
+		svg,
+		r#"<defs><linearGradient id="bouncy" x1="0" y1="1" x2="1" y2="0"><stop offset="0" stop-color="{0}"/><stop offset="0.5" stop-color="{1}"/><stop offset="1" stop-color="{0}"/></linearGradient><radialGradient id="shine" cx="0.7" cy="0.1" r="1"><stop offset="0" stop-color="rgb(248,248,248)" stop-opacity="0.85"/><stop offset="1" stop-color="rgb(0,248,248)" stop-opacity="0"/></radialGradient></defs>"#,
+		rgb(BOUNCY_COLORS[0]),
+		rgb(BOUNCY_COLORS[1]),
+	);
+	let _ = writeln!(
+		svg,
+		r#"<rect width="{image_width}" height="{image_height}" rx="8" fill="{}"/>"#,
+		rgb(BACKGROUND)
+	);
+
+	for y in 0..height {
+		for x in 0..width {
+			let kind = cells.get(y, x).unwrap();
+			let (top, left) = (PADDING + y * PITCH, PADDING + x * PITCH);
+			let fill = if kind == CellKind::BouncyWall {
+				String::from("url(#bouncy)")
+			} else {
+				rgb(get_fill(kind))
+			};
+			let _ = writeln!(
+				svg,
+				r#"<rect x="{left}" y="{top}" width="{CELL_SIZE}" height="{CELL_SIZE}" rx="{CORNER_RADIUS}" fill="{fill}"/>"#
+			);
+			if kind == CellKind::BouncyWall {
+				let _ = writeln!(
+					svg,
+					r#"<rect x="{}" y="{}" width="48" height="48" rx="{CORNER_RADIUS}" fill="url(#shine)"/>"#,
+					left + 8,
+					top + 8
+				);
+			}
+		}
+	}
+
+	if let Some(trace) = trace {
+		for (i, &path) in trace.iter().enumerate() {
+			let (y, x) = (i / width, i % width);
+			let (top, left) = (
+				PADDING + y * PITCH - PATH_MARGIN,
+				PADDING + x * PITCH - PATH_MARGIN,
+			);
+			let (bottom, right) = (
+				top + PITCH + 2 * PATH_MARGIN - GAP,
+				left + PITCH + 2 * PATH_MARGIN - GAP,
+			);
+			for (bit, (y1, y2)) in [(top, bottom), (bottom, top)].into_iter().enumerate() {
+				if path & 1 << bit != 0 {
+					let _ = writeln!(
+						svg,
+						r#"<line x1="{left}" y1="{y1}" x2="{right}" y2="{y2}" stroke="{}" stroke-width="{PATH_WIDTH}"/>"#,
+						rgb(PATH_COLOR)
+					);
+				}
+			}
+		}
+	}
+
+	if let Some(Marker { y, x, direction }) = marker {
+		let (cy, cx) = (
+			PADDING + y * PITCH + CELL_SIZE / 2,
+			PADDING + x * PITCH + CELL_SIZE / 2,
+		);
+		let _ = writeln!(
+			svg,
+			r#"<circle cx="{cx}" cy="{cy}" r="12" fill="{}" stroke="{}" stroke-width="3"/>"#,
+			rgb(MARKER_COLOR),
+			rgb(MARKER_BORDER)
+		);
+		if let Some(direction) = direction {
+			let (dy, dx) = direction.get_delta();
+			let point = |along: f64, across: f64| {
+				// `along` runs in the direction of travel, `across` perpendicular to it
+				let (uy, ux) = (dy as f64 / 2f64.sqrt(), dx as f64 / 2f64.sqrt());
+				(
+					cx as f64 + ux * along - uy * across,
+					cy as f64 + uy * along + ux * across,
+				)
+			};
+			let (x1, y1) = point(12.0, 0.0);
+			let (x2, y2) = point(30.0, 0.0);
+			let head = [point(42.0, 0.0), point(28.0, 9.0), point(28.0, -9.0)];
+			let _ = writeln!(
+				svg,
+				r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{}" stroke-width="{PATH_WIDTH}"/>"#,
+				rgb(MARKER_COLOR)
+			);
+			let _ = writeln!(
+				svg,
+				r#"<polygon points="{}" fill="{}" stroke="{}" stroke-width="2"/>"#,
+				head.iter()
+					.map(|(x, y)| format!("{x:.1},{y:.1}"))
+					.collect::<Vec<_>>()
+					.join(" "),
+				rgb(MARKER_COLOR),
+				rgb(MARKER_BORDER)
+			);
+		}
+	}
+
+	svg.push_str("</svg>\n");
+	svg
+}
+
+struct Canvas {
+	pixels: Vec<[u8; 3]>,
+	width: usize,
+	height: usize,
+}
+
+impl Canvas {
+	fn new(height: usize, width: usize, color: [u8; 3]) -> Self {
+		Self {
+			pixels: vec![color; height * width],
+			width,
+			height,
+		}
+	}
+
+	fn fill<F>(&mut self, top: isize, left: isize, size: usize, mut f: F)
+	where
+		F: FnMut(usize, usize) -> Option<[u8; 3]>,
+	{
+		for dy in 0..size {
+			for dx in 0..size {
+				let (Some(y), Some(x)) = (
+					top.checked_add_unsigned(dy)
+						.and_then(|y| usize::try_from(y).ok()),
+					left.checked_add_unsigned(dx)
+						.and_then(|x| usize::try_from(x).ok()),
+				) else {
+					continue;
+				};
+				if y >= self.height || x >= self.width {
+					continue;
+				}
+				if let Some(color) = f(dy, dx) {
+					self.pixels[y * self.width + x] = color;
+				}
+			}
+		}
+	}
+
+	fn to_rgb(&self) -> Vec<u8> {
+		self.pixels.iter().flatten().copied().collect()
+	}
+}
+
+fn lerp(a: [u8; 3], b: [u8; 3], t: f64) -> [u8; 3] {
+	[0, 1, 2].map(|i| (a[i] as f64 + (b[i] as f64 - a[i] as f64) * t).round() as u8)
+}
+
+/// Renders the same picture as [`to_svg`] into a PNG file.
+pub fn to_png(cells: &Cells, trace: Option<&[u8]>, marker: Option<Marker>) -> Vec<u8> {
+	let (height, width) = cells.get_size();
+	let (image_height, image_width) = get_image_size(cells);
+	let mut canvas = Canvas::new(image_height, image_width, BACKGROUND);
+
+	let is_inside_rounded = |dy: usize, dx: usize| {
+		let r = CORNER_RADIUS as isize;
+		let distance = |d: usize| {
+			let d = d as isize;
+			(r - d).max(d - (CELL_SIZE as isize - 1 - r)).max(0)
+		};
+		let (ey, ex) = (distance(dy), distance(dx));
+		ey * ey + ex * ex <= r * r
+	};
+
+	for y in 0..height {
+		for x in 0..width {
+			let kind = cells.get(y, x).unwrap();
+			let (top, left) = (
+				(PADDING + y * PITCH) as isize,
+				(PADDING + x * PITCH) as isize,
+			);
+			canvas.fill(top, left, CELL_SIZE, |dy, dx| {
+				if !is_inside_rounded(dy, dx) {
+					return None;
+				}
+				if kind != CellKind::BouncyWall {
+					return Some(get_fill(kind));
+				}
+				// 45deg gradient, pink -> blue -> pink from the bottom left corner
+				let t = (dx + CELL_SIZE - 1 - dy) as f64 / (2 * (CELL_SIZE - 1)) as f64;
+				Some(if t < 0.5 {
+					lerp(BOUNCY_COLORS[0], BOUNCY_COLORS[1], t * 2.0)
+				} else {
+					lerp(BOUNCY_COLORS[1], BOUNCY_COLORS[0], t * 2.0 - 1.0)
+				})
+			});
+		}
+	}
+
+	if let Some(trace) = trace {
+		let size = CELL_SIZE + 2 * PATH_MARGIN;
+		for (i, &path) in trace.iter().enumerate() {
+			let (y, x) = (i / width, i % width);
+			let top = (PADDING + y * PITCH) as isize - PATH_MARGIN as isize;
+			let left = (PADDING + x * PITCH) as isize - PATH_MARGIN as isize;
+			canvas.fill(top, left, size, |dy, dx| {
+				let diagonal1 = path & 1 != 0 && dy.abs_diff(dx) <= PATH_WIDTH * 2 / 3;
+				let diagonal2 = path & 2 != 0 && (dy + dx).abs_diff(size - 1) <= PATH_WIDTH * 2 / 3;
+				(diagonal1 || diagonal2).then_some(PATH_COLOR)
+			});
+		}
+	}
+
+	if let Some(Marker { y, x, direction }) = marker {
+		let top = (PADDING + y * PITCH) as isize;
+		let left = (PADDING + x * PITCH) as isize;
+		let center = (CELL_SIZE / 2) as f64;
+		canvas.fill(top, left, CELL_SIZE, |dy, dx| {
+			let distance = (dy as f64 - center).hypot(dx as f64 - center);
+			if distance <= 10.5 {
+				Some(MARKER_COLOR)
+			} else if distance <= 13.5 {
+				Some(MARKER_BORDER)
+			} else {
+				None
+			}
+		});
+		if let Some(direction) = direction {
+			let (uy, ux) = direction.get_delta();
+			let (uy, ux) = (uy as f64 / 2f64.sqrt(), ux as f64 / 2f64.sqrt());
+			// the arrow sticks out of the cell, so paint over the neighbours as well
+			canvas.fill(
+				top - PITCH as isize,
+				left - PITCH as isize,
+				PITCH * 3,
+				|dy, dx| {
+					let py = dy as f64 - PITCH as f64 - center;
+					let px = dx as f64 - PITCH as f64 - center;
+					let along = py * uy + px * ux;
+					let across = (px * uy - py * ux).abs();
+					let shaft = (12.0..=30.0).contains(&along) && across <= PATH_WIDTH as f64 / 2.0;
+					let head =
+						(28.0..=42.0).contains(&along) && across <= (42.0 - along) * 9.0 / 14.0;
+					(shaft || head).then_some(MARKER_COLOR)
+				},
+			);
+		}
+	}
+
+	encode_png(image_height, image_width, &canvas.to_rgb())
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &byte in bytes {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = if crc & 1 != 0 {
+				crc >> 1 ^ 0xedb8_8320
+			} else {
+				crc >> 1
+			};
+		}
+	}
+	!crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+	let (mut a, mut b) = (1u32, 0u32);
+	for &byte in bytes {
+		a = (a + byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+	b << 16 | a
+}
+
+struct BitWriter {
+	bytes: Vec<u8>,
+	buffer: u32,
+	length: u32,
+}
+
+impl BitWriter {
+	fn write_bits(&mut self, value: u32, length: u32) {
+		self.buffer |= value << self.length;
+		self.length += length;
+		while self.length >= 8 {
+			self.bytes.push(self.buffer as u8);
+			self.buffer >>= 8;
+			self.length -= 8;
+		}
+	}
+
+	// Huffman codes are packed starting from the most significant bit
+	fn write_code(&mut self, code: u32, length: u32) {
+		self.write_bits(code.reverse_bits() >> (32 - length), length);
+	}
+
+	fn write_symbol(&mut self, symbol: u32) {
+		match symbol {
+			0..=143 => self.write_code(0x30 + symbol, 8),
+			144..=255 => self.write_code(0x190 + symbol - 144, 9),
+			256..=279 => self.write_code(symbol - 256, 7),
+			_ => self.write_code(0xc0 + symbol - 280, 8),
+		}
+	}
+
+	fn finish(mut self) -> Vec<u8> {
+		if self.length > 0 {
+			self.bytes.push(self.buffer as u8);
+		}
+		self.bytes
+	}
+}
+
+// Deflate with the fixed Huffman table. Instead of searching a whole window, matches are only
+// looked for at the given distances; for a picture made of flat colours and diagonal stripes
+// the previous pixel and the neighbours in the previous row are enough.
+fn deflate(data: &[u8], distances: &[usize]) -> Vec<u8> {
+	const LENGTH_BASES: [u32; 29] = [
+		3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+		131, 163, 195, 227, 258,
+	];
+	const LENGTH_EXTRA_BITS: [u32; 29] = [
+		0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+	];
+	const DISTANCE_BASES: [u32; 30] = [
+		1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+		2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+	];
+	const DISTANCE_EXTRA_BITS: [u32; 30] = [
+		0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+		13, 13,
+	];
+	const MAX_LENGTH: usize = 258;
+	const MAX_DISTANCE: usize = 32768;
+
+	let mut writer = BitWriter {
+		bytes: vec![0x78, 0x01],
+		buffer: 0,
+		length: 0,
+	};
+	// BFINAL = 1, BTYPE = 01 (fixed Huffman)
+	writer.write_bits(0b011, 3);
+
+	let mut i = 0;
+	while i < data.len() {
+		let (length, distance) = distances
+			.iter()
+			.filter(|&&distance| (1..=i.min(MAX_DISTANCE)).contains(&distance))
+			.map(|&distance| {
+				let length = data[i..]
+					.iter()
+					.take(MAX_LENGTH)
+					.zip(&data[i - distance..])
+					.take_while(|(a, b)| a == b)
+					.count();
+				(length, distance)
+			})
+			.max_by_key(|&(length, _)| length)
+			.unwrap_or((0, 0));
+
+		if length >= 3 {
+			let code = LENGTH_BASES.partition_point(|&base| base <= length as u32) - 1;
+			writer.write_symbol(257 + code as u32);
+			writer.write_bits(length as u32 - LENGTH_BASES[code], LENGTH_EXTRA_BITS[code]);
+			let code = DISTANCE_BASES.partition_point(|&base| base <= distance as u32) - 1;
+			writer.write_code(code as u32, 5);
+			writer.write_bits(
+				distance as u32 - DISTANCE_BASES[code],
+				DISTANCE_EXTRA_BITS[code],
+			);
+			i += length;
+		} else {
+			writer.write_symbol(data[i] as u32);
+			i += 1;
+		}
+	}
+	writer.write_symbol(256);
+
+	let mut bytes = writer.finish();
+	bytes.extend_from_slice(&adler32(data).to_be_bytes());
+	bytes
+}
+
+fn encode_png(height: usize, width: usize, rgb: &[u8]) -> Vec<u8> {
+	const BYTES_PER_PIXEL: usize = 3;
+
+	let stride = width * BYTES_PER_PIXEL;
+	let mut scanlines = Vec::with_capacity(height * (stride + 1));
+	for row in rgb.chunks(stride) {
+		// filter type 0 (None)
+		scanlines.push(0);
+		scanlines.extend_from_slice(row);
+	}
+
+	let mut header = Vec::with_capacity(13);
+	header.extend_from_slice(&(width as u32).to_be_bytes());
+	header.extend_from_slice(&(height as u32).to_be_bytes());
+	// bit depth 8, colour type 2 (RGB), deflate, adaptive filtering, no interlace
+	header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+	let row = stride + 1;
+	let distances = [
+		BYTES_PER_PIXEL,
+		row,
+		row - BYTES_PER_PIXEL,
+		row + BYTES_PER_PIXEL,
+	];
+
+	let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+	for (kind, data) in [
+		(b"IHDR", header),
+		(b"IDAT", deflate(&scanlines, &distances)),
+		(b"IEND", Vec::new()),
+	] {
+		png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+		let start = png.len();
+		png.extend_from_slice(kind);
+		png.extend_from_slice(&data);
+		let crc = crc32(&png[start..]);
+		png.extend_from_slice(&crc.to_be_bytes());
+	}
+	png
+}
+
+#[cfg(test)]
+mod tests {
+	use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+	use super::*;
+
+	#[test]
+	fn checksums_match_known_values() {
+		assert_eq!(crc32(b""), 0);
+		assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+		assert_eq!(crc32(b"IEND"), 0xae42_6082);
+		assert_eq!(adler32(b""), 1);
+		assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+	}
+
+	#[test]
+	fn deflate_inflates_back() {
+		let mut data: Vec<u8> = (0..=255).collect();
+		// runs longer than one match can cover
+		data.extend([7; 1000]);
+		data.extend((0..2000).map(|i| (i % 3 * 40 + i / 500) as u8));
+
+		for distances in [&[][..], &[1, 3, 255, 256]] {
+			let compressed = deflate(&data, distances);
+			assert_eq!(decompress_to_vec_zlib(&compressed).unwrap(), data);
+		}
+	}
+
+	#[test]
+	fn png_decodes() {
+		let cells = Cells::try_from("#b.\n.,w").unwrap();
+		let (height, width) = get_image_size(&cells);
+		let png = to_png(&cells, None, None);
+
+		assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+		let mut chunks = Vec::new();
+		let mut rest = &png[8..];
+		while !rest.is_empty() {
+			let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+			let (body, crc) = rest[4..].split_at(4 + length);
+			assert_eq!(crc32(body).to_be_bytes(), crc[..4]);
+			chunks.push((&body[..4], &body[4..]));
+			rest = &crc[4..];
+		}
+		let kinds: Vec<_> = chunks.iter().map(|&(kind, _)| kind).collect();
+		assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+		assert_eq!(
+			chunks[0].1[..8],
+			[
+				&(width as u32).to_be_bytes()[..],
+				&(height as u32).to_be_bytes()[..]
+			]
+			.concat()
+		);
+
+		let scanlines = decompress_to_vec_zlib(chunks[1].1).unwrap();
+		let stride = width * 3 + 1;
+		assert_eq!(scanlines.len(), height * stride);
+		assert!(scanlines.chunks(stride).all(|row| row[0] == 0));
+		let pixel = |y: usize, x: usize| scanlines[y * stride + 1 + x * 3..][..3].to_vec();
+		assert_eq!(pixel(0, 0), BACKGROUND);
+		// the middle of each cell
+		let center = |y: usize, x: usize| {
+			pixel(
+				PADDING + y * PITCH + CELL_SIZE / 2,
+				PADDING + x * PITCH + CELL_SIZE / 2,
+			)
+		};
+		assert_eq!(center(0, 0), get_fill(CellKind::Wall));
+		assert_eq!(center(1, 1), get_fill(CellKind::Pit));
+		assert_eq!(center(1, 2), get_fill(CellKind::Water));
+	}
+}
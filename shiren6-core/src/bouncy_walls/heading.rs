@@ -0,0 +1,74 @@
+/// One of the eight directions a player, monster or projectile can face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heading {
+	Up,
+	UpRight,
+	Right,
+	DownRight,
+	Down,
+	DownLeft,
+	Left,
+	UpLeft,
+}
+
+impl Heading {
+	pub const ALL: [Self; 8] = [
+		Self::Up,
+		Self::UpRight,
+		Self::Right,
+		Self::DownRight,
+		Self::Down,
+		Self::DownLeft,
+		Self::Left,
+		Self::UpLeft,
+	];
+
+	pub fn get_arrow(&self) -> &'static str {
+		use Heading::*;
+
+		match self {
+			Up => "↑",
+			UpRight => "↗",
+			Right => "→",
+			DownRight => "↘",
+			Down => "↓",
+			DownLeft => "↙",
+			Left => "←",
+			UpLeft => "↖",
+		}
+	}
+
+	pub fn get_delta(&self) -> (isize, isize) {
+		use Heading::*;
+
+		match self {
+			Up => (-1, 0),
+			UpRight => (-1, 1),
+			Right => (0, 1),
+			DownRight => (1, 1),
+			Down => (1, 0),
+			DownLeft => (1, -1),
+			Left => (0, -1),
+			UpLeft => (-1, -1),
+		}
+	}
+
+	pub fn from_delta(dy: isize, dx: isize) -> Option<Self> {
+		Self::ALL
+			.into_iter()
+			.find(|heading| heading.get_delta() == (dy, dx))
+	}
+
+	pub fn is_diagonal(&self) -> bool {
+		let (dy, dx) = self.get_delta();
+		dy != 0 && dx != 0
+	}
+
+	/// The tile one step ahead of `(y, x)`, if it is on a map of the given size.
+	pub fn step(&self, y: usize, x: usize, height: usize, width: usize) -> Option<(usize, usize)> {
+		let (dy, dx) = self.get_delta();
+		y.checked_add_signed(dy)
+			.filter(|&y| y < height)
+			.zip(x.checked_add_signed(dx).filter(|&x| x < width))
+	}
+}
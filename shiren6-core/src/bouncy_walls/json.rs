@@ -0,0 +1,210 @@
+//! JSON form of a map and its analysis, for other tools to consume.
+//!
+//! The schema is stable within a `version`; fields are only ever added.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "map": {
+//!     "name": "とぐろ島",             // optional
+//!     "floor": 12,                   // optional
+//!     "notes": ["..."],              // optional
+//!     "height": 7,
+//!     "width": 11,
+//!     "cells": ["wall", "bouncy_wall", "vacant", "pit", "water", ...],
+//!     "entities": [{ "kind": "player", "y": 1, "x": 4 }],
+//!     "comments": [{ "row": 0, "text": "..." }]
+//!   },
+//!   "routes": [
+//!     { "representative": 24, "layer": 0, "y": 2, "x": 2, "trace": [0, 1, 2, ...] }
+//!   ]
+//! }
+//! ```
+//!
+//! - `cells` holds `height * width` kinds in row-major order. Kinds are `vacant`, `wall`, `pit`,
+//!   `water` and `bouncy_wall`.
+//! - `entities[].kind` is one of `player`, `stairs`, `monster` and `fast_monster`, and stands
+//!   on a vacant cell. There is at most one `player` and one `stairs`.
+//! - `routes` lists every success path found by `Cells::find_routes`. `representative` is the
+//!   vertex the search started from: `layer * height * width + y * width + x`, where layer 0
+//!   runs along `\` and layer 1 along `/`.
+//! - `trace` has one bitmask per cell in row-major order, as returned by `Cells::trace`:
+//!   bit 0 (`1`) when the path crosses the cell along `\`, bit 1 (`2`) along `/`.
+//! - `routes` is ignored on import and recomputed from the map.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+	cell::{CellKind, Cells},
+	map::{Comment, Entity, Header, Map},
+};
+
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CellsJson {
+	height: usize,
+	width: usize,
+	cells: Vec<CellKind>,
+}
+
+impl Serialize for Cells {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let (height, width) = self.get_size();
+		CellsJson {
+			height,
+			width,
+			cells: self.kinds().to_vec(),
+		}
+		.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Cells {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let CellsJson {
+			height,
+			width,
+			cells,
+		} = CellsJson::deserialize(deserializer)?;
+		Cells::from_kinds(height, width, cells)
+			.map_err(|e| de::Error::custom(format!("invalid cells: {e:?}")))
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct MapJson {
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	floor: Option<u32>,
+	#[serde(default)]
+	notes: Vec<String>,
+	#[serde(flatten)]
+	cells: Cells,
+	#[serde(default)]
+	entities: Vec<Entity>,
+	#[serde(default)]
+	comments: Vec<Comment>,
+}
+
+impl Serialize for Map {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let Header { name, floor, notes } = self.header.clone();
+		MapJson {
+			name,
+			floor,
+			notes,
+			cells: self.cells.clone(),
+			entities: self.entities.clone(),
+			comments: self.comments.clone(),
+		}
+		.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Map {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let MapJson {
+			name,
+			floor,
+			notes,
+			cells,
+			entities,
+			comments,
+		} = MapJson::deserialize(deserializer)?;
+
+		for entity in entities.iter() {
+			if cells.get(entity.y, entity.x) != Some(CellKind::Vacant) {
+				return Err(de::Error::custom("entities must stand on vacant cells"));
+			}
+		}
+		for (i, entity) in entities.iter().enumerate() {
+			if entity.kind.is_unique()
+				&& entities[..i].iter().any(|other| other.kind == entity.kind)
+			{
+				return Err(de::Error::custom("more than one player or stairs"));
+			}
+		}
+		let (height, _) = cells.get_size();
+		if comments.iter().any(|comment| comment.row > height) {
+			return Err(de::Error::custom("comment row out of range"));
+		}
+
+		Ok(Map {
+			header: Header { name, floor, notes },
+			cells,
+			entities,
+			comments,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Route {
+	pub representative: usize,
+	pub layer: usize,
+	pub y: usize,
+	pub x: usize,
+	pub trace: Vec<u8>,
+}
+
+impl Route {
+	pub fn new(cells: &Cells, representative: usize) -> Self {
+		let (_, width) = cells.get_size();
+		let v = representative % cells.len();
+		Self {
+			representative,
+			layer: representative / cells.len(),
+			y: v / width,
+			x: v % width,
+			trace: cells.trace(representative),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Document {
+	pub version: u32,
+	pub map: Map,
+	#[serde(default)]
+	pub routes: Vec<Route>,
+}
+
+/// Serialises the map together with all of its success paths.
+pub fn export(map: &Map) -> String {
+	let document = Document {
+		version: VERSION,
+		map: map.clone(),
+		routes: map
+			.cells
+			.find_routes()
+			.into_iter()
+			.map(|v| Route::new(&map.cells, v))
+			.collect(),
+	};
+	serde_json::to_string_pretty(&document).expect("Could not serialise the map.")
+}
+
+pub fn import(json: &str) -> Result<Map, serde_json::Error> {
+	let document: Document = serde_json::from_str(json)?;
+	if document.version != VERSION {
+		return Err(de::Error::custom(format!(
+			"unsupported version: {}",
+			document.version
+		)));
+	}
+	Ok(document.map)
+}
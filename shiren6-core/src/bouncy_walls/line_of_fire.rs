@@ -0,0 +1,155 @@
+use super::{
+	cell::{CellKind, Cells},
+	heading::Heading,
+	map::{EntityKind, Map},
+};
+
+/// How a projectile reaches a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+	/// the direction to shoot in
+	pub heading: Heading,
+	/// reflections off bouncy walls on the way
+	pub bounces: usize,
+	/// tiles travelled
+	pub distance: usize,
+}
+
+/// Shoots in all eight directions from `(y, x)` and returns, for every tile in row-major
+/// order, the shot reaching it with the fewest bounces and then the shortest distance.
+///
+/// Projectiles fly over pits and water and stop at walls and monsters. Bouncy walls reflect
+/// them: straight back on a straight shot, and on a diagonal shot the same way `Cells::dfs`
+/// turns, off the two bouncy walls forming the surface. A shot reflected back to `(y, x)`
+/// hits the shooter and is recorded there.
+pub fn line_of_fire(map: &Map, y: usize, x: usize) -> Vec<Option<Hit>> {
+	let cells = &map.cells;
+	let (_, width) = cells.get_size();
+	let mut hits: Vec<Option<Hit>> = vec![None; cells.len()];
+
+	for heading in Heading::ALL {
+		// a projectile can loop between bouncy walls forever
+		let mut visited = vec![[false; 8]; cells.len()];
+		let mut shot = Shot {
+			y,
+			x,
+			heading,
+			bounces: 0,
+			distance: 0,
+		};
+
+		while let Some(next) = shot.advance(cells) {
+			shot = next;
+			let i = shot.y * width + shot.x;
+			let h = Heading::ALL
+				.iter()
+				.position(|&h| h == shot.heading)
+				.unwrap();
+			if visited[i][h] {
+				break;
+			}
+			visited[i][h] = true;
+
+			let hit = Hit {
+				heading,
+				bounces: shot.bounces,
+				distance: shot.distance,
+			};
+			let better = hits[i]
+				.is_none_or(|best| (hit.bounces, hit.distance) < (best.bounces, best.distance));
+			if better {
+				hits[i] = Some(hit);
+			}
+
+			if (shot.y, shot.x) == (y, x)
+				|| matches!(
+					map.get_entity(shot.y, shot.x),
+					Some(EntityKind::Monster | EntityKind::FastMonster)
+				) {
+				break;
+			}
+		}
+	}
+
+	hits
+}
+
+#[derive(Clone, Copy)]
+struct Shot {
+	y: usize,
+	x: usize,
+	heading: Heading,
+	bounces: usize,
+	distance: usize,
+}
+
+impl Shot {
+	fn advance(self, cells: &Cells) -> Option<Self> {
+		let (height, width) = cells.get_size();
+		let kind = |(y, x): (usize, usize)| cells.get(y, x).unwrap();
+		let (dy, dx) = self.heading.get_delta();
+
+		let target = self.heading.step(self.y, self.x, height, width)?;
+		if !kind(target).is_solid() {
+			return Some(Self {
+				y: target.0,
+				x: target.1,
+				distance: self.distance + 1,
+				..self
+			});
+		}
+
+		let bounce = |(dy, dx), (y, x), distance| {
+			Some(Self {
+				y,
+				x,
+				heading: Heading::from_delta(dy, dx).unwrap(),
+				bounces: self.bounces + 1,
+				distance,
+			})
+		};
+		let bouncy = |tile| kind(tile) == CellKind::BouncyWall;
+
+		if !self.heading.is_diagonal() {
+			return if bouncy(target) {
+				bounce((-dy, -dx), (self.y, self.x), self.distance)
+			} else {
+				None
+			};
+		}
+
+		let adj_y = (target.0, self.x);
+		let adj_x = (self.y, target.1);
+		match (kind(adj_y).is_solid(), kind(adj_x).is_solid()) {
+			(true, false) if bouncy(target) && bouncy(adj_y) => {
+				bounce((-dy, dx), adj_x, self.distance + 1)
+			}
+			(false, true) if bouncy(target) && bouncy(adj_x) => {
+				bounce((dy, -dx), adj_y, self.distance + 1)
+			}
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reflected_shot_hits_shooter() {
+		let map = Map::try_from("..@b\n....").unwrap();
+		let hits = line_of_fire(&map, 0, 2);
+
+		assert_eq!(
+			hits[2],
+			Some(Hit {
+				heading: Heading::Right,
+				bounces: 1,
+				distance: 0,
+			})
+		);
+		// only the shot to the left gets past the shooter
+		assert_eq!(hits[0].map(|hit| hit.heading), Some(Heading::Left));
+	}
+}
@@ -0,0 +1,394 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::cell::{CellKind, Cells, CellsError};
+
+// The versioned format looks like this:
+//
+// !shiren6-map v1
+// name: とぐろ島
+// floor: 12
+// note: 北東の部屋
+// // comment
+// #bbbbbbbb##
+// bb..@...bbb
+//
+// Without the first line the text is read as a bare map, the format the textarea has always
+// accepted. Comments are only recognised in the versioned format, since `//` is also a valid
+// row of a path overlay.
+const MAGIC: &str = "!shiren6-map";
+const VERSION: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+	Player,
+	Stairs,
+	Monster,
+	FastMonster,
+}
+
+impl EntityKind {
+	pub fn get_char(&self) -> char {
+		use EntityKind::*;
+
+		match self {
+			Player => '@',
+			Stairs => '>',
+			Monster => 'm',
+			FastMonster => 'M',
+		}
+	}
+
+	pub fn get_class_name(&self) -> &'static str {
+		use EntityKind::*;
+
+		match self {
+			Player => "player",
+			Stairs => "stairs",
+			Monster => "monster",
+			FastMonster => "fast_monster",
+		}
+	}
+
+	/// Whether a map holds at most one of it.
+	pub fn is_unique(&self) -> bool {
+		matches!(self, EntityKind::Player | EntityKind::Stairs)
+	}
+
+	pub fn from_char(c: char) -> Option<Self> {
+		use EntityKind::*;
+
+		match c {
+			'@' => Some(Player),
+			'>' => Some(Stairs),
+			'm' => Some(Monster),
+			'M' => Some(FastMonster),
+			_ => None,
+		}
+	}
+}
+
+/// Something standing on a cell. The cell underneath is always vacant.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entity {
+	pub kind: EntityKind,
+	pub y: usize,
+	pub x: usize,
+}
+
+/// A `//` line, kept with the number of rows above it.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+	pub row: usize,
+	pub text: String,
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Header {
+	pub name: Option<String>,
+	pub floor: Option<u32>,
+	pub notes: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Map {
+	pub header: Header,
+	pub cells: Cells,
+	pub entities: Vec<Entity>,
+	pub comments: Vec<Comment>,
+}
+
+pub type MapResult = Result<Map, MapError>;
+
+impl Map {
+	pub fn get_entity(&self, y: usize, x: usize) -> Option<EntityKind> {
+		self.entities
+			.iter()
+			.find(|entity| entity.y == y && entity.x == x)
+			.map(|entity| entity.kind)
+	}
+
+	/// Sets the cell kind, removing the entity there unless the cell stays vacant.
+	pub fn set_cell(&mut self, y: usize, x: usize, kind: CellKind) {
+		self.cells.set(y, x, kind);
+		if kind != CellKind::Vacant {
+			self.set_entity(y, x, None);
+		}
+	}
+
+	/// Places or removes the entity. The cell underneath becomes vacant. Placing the player or
+	/// the stairs moves it from where it was.
+	pub fn set_entity(&mut self, y: usize, x: usize, kind: Option<EntityKind>) {
+		self.entities.retain(|entity| {
+			(entity.y != y || entity.x != x)
+				&& !(entity.kind.is_unique() && Some(entity.kind) == kind)
+		});
+		if let Some(kind) = kind {
+			self.cells.set(y, x, CellKind::Vacant);
+			self.entities.push(Entity { kind, y, x });
+		}
+	}
+
+	/// Inserts a row of vacant cells above row `y`. Comments above the row stay above the new
+	/// one.
+	pub fn insert_row(&mut self, y: usize) -> Result<(), CellsError> {
+		self.cells.insert_row(y)?;
+		for entity in self.entities.iter_mut().filter(|entity| entity.y >= y) {
+			entity.y += 1;
+		}
+		for comment in self.comments.iter_mut().filter(|comment| comment.row > y) {
+			comment.row += 1;
+		}
+		Ok(())
+	}
+
+	/// Removes row `y` and the entities on it unless it is the only row.
+	pub fn remove_row(&mut self, y: usize) {
+		let (height, _) = self.cells.get_size();
+		if height <= 1 {
+			return;
+		}
+		self.cells.remove_row(y);
+		self.entities.retain(|entity| entity.y != y);
+		for entity in self.entities.iter_mut().filter(|entity| entity.y > y) {
+			entity.y -= 1;
+		}
+		for comment in self.comments.iter_mut().filter(|comment| comment.row > y) {
+			comment.row -= 1;
+		}
+	}
+
+	/// Inserts a column of vacant cells left of column `x`.
+	pub fn insert_column(&mut self, x: usize) -> Result<(), CellsError> {
+		self.cells.insert_column(x)?;
+		for entity in self.entities.iter_mut().filter(|entity| entity.x >= x) {
+			entity.x += 1;
+		}
+		Ok(())
+	}
+
+	/// Removes column `x` and the entities on it unless it is the only column.
+	pub fn remove_column(&mut self, x: usize) {
+		let (_, width) = self.cells.get_size();
+		if width <= 1 {
+			return;
+		}
+		self.cells.remove_column(x);
+		self.entities.retain(|entity| entity.x != x);
+		for entity in self.entities.iter_mut().filter(|entity| entity.x > x) {
+			entity.x -= 1;
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+	Cells(CellsError),
+	UnsupportedVersion,
+	UnknownKey,
+	InvalidFloor,
+	HeaderAfterRows,
+	// a second player or stairs
+	DuplicateEntity,
+}
+
+impl From<CellsError> for MapError {
+	fn from(value: CellsError) -> Self {
+		Self::Cells(value)
+	}
+}
+
+impl TryFrom<&str> for Map {
+	type Error = MapError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let mut lines = value.lines().skip_while(|line| line.is_empty()).peekable();
+
+		let versioned = match lines.peek() {
+			Some(line) if line.starts_with(MAGIC) => {
+				let version = line[MAGIC.len()..].trim().strip_prefix('v');
+				if version.and_then(|version| version.parse().ok()) != Some(VERSION) {
+					return Err(MapError::UnsupportedVersion);
+				}
+				lines.next();
+				true
+			}
+			_ => false,
+		};
+
+		let mut header = Header::default();
+		let mut comments = Vec::new();
+		let mut entities = Vec::new();
+		let mut rows: Vec<String> = Vec::new();
+
+		for line in lines {
+			if versioned {
+				if let Some(comment) = line.strip_prefix("//") {
+					// only the space `Display` writes after the slashes is dropped
+					let text = comment.strip_prefix(' ').unwrap_or(comment);
+					comments.push(Comment {
+						row: rows.len(),
+						text: String::from(text),
+					});
+					continue;
+				}
+				// rows never contain ':'
+				if let Some((key, value)) = line.split_once(':') {
+					if !rows.is_empty() {
+						return Err(MapError::HeaderAfterRows);
+					}
+					let value = value.trim();
+					match key.trim() {
+						"name" => header.name = Some(String::from(value)),
+						"floor" => {
+							header.floor = Some(value.parse().map_err(|_| MapError::InvalidFloor)?)
+						}
+						"note" => header.notes.push(String::from(value)),
+						_ => return Err(MapError::UnknownKey),
+					}
+					continue;
+				}
+				if rows.is_empty() && line.is_empty() {
+					continue;
+				}
+			}
+
+			let y = rows.len();
+			let mut row = String::new();
+			for (x, c) in line.chars().enumerate() {
+				match EntityKind::from_char(c) {
+					Some(kind) => {
+						if kind.is_unique()
+							&& entities.iter().any(|entity: &Entity| entity.kind == kind)
+						{
+							return Err(MapError::DuplicateEntity);
+						}
+						entities.push(Entity { kind, y, x });
+						row.push('.');
+					}
+					None => row.push(c),
+				}
+			}
+			rows.push(row);
+		}
+
+		let cells = Cells::try_from(rows.join("\n").as_str())?;
+		// trailing empty rows are dropped by the parser
+		let (height, _) = cells.get_size();
+		for comment in comments.iter_mut() {
+			comment.row = comment.row.min(height);
+		}
+
+		Ok(Self {
+			header,
+			cells,
+			entities,
+			comments,
+		})
+	}
+}
+
+impl fmt::Display for Map {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{MAGIC} v{VERSION}")?;
+		if let Some(name) = self.header.name.as_ref() {
+			writeln!(f, "name: {name}")?;
+		}
+		if let Some(floor) = self.header.floor {
+			writeln!(f, "floor: {floor}")?;
+		}
+		for note in self.header.notes.iter() {
+			writeln!(f, "note: {note}")?;
+		}
+
+		let (height, width) = self.cells.get_size();
+		let write_comments = |f: &mut fmt::Formatter<'_>, rows: &dyn Fn(usize) -> bool| {
+			for comment in self.comments.iter().filter(|comment| rows(comment.row)) {
+				writeln!(f, "// {}", comment.text)?;
+			}
+			Ok(())
+		};
+
+		for y in 0..height {
+			write_comments(f, &|row| row == y)?;
+			for x in 0..width {
+				let c = match self.get_entity(y, x) {
+					Some(entity) => entity.get_char(),
+					None => self.cells.get(y, x).unwrap().get_char(),
+				};
+				write!(f, "{c}")?;
+			}
+			writeln!(f)?;
+		}
+		write_comments(f, &|row| row == height)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn versioned_text_round_trips() {
+		let text = "\
+!shiren6-map v1
+name: 跳ね返る壁の部屋
+floor: 12
+note: 罠あり
+note: 二つ目のメモ
+//  上の部屋
+@.b#
+// 水路
+~~.m
+.M,>
+// 最後
+";
+		let map = Map::try_from(text).unwrap_or_else(|_| panic!());
+		assert_eq!(map.header.name.as_deref(), Some("跳ね返る壁の部屋"));
+		assert_eq!(map.header.floor, Some(12));
+		assert_eq!(map.header.notes, ["罠あり", "二つ目のメモ"]);
+		let comments: Vec<_> = map
+			.comments
+			.iter()
+			.map(|comment| (comment.row, comment.text.as_str()))
+			.collect();
+		assert_eq!(comments, [(0, " 上の部屋"), (1, "水路"), (3, "最後")]);
+		assert_eq!(map.get_entity(0, 0).map(|kind| kind.get_char()), Some('@'));
+		assert_eq!(map.get_entity(1, 3).map(|kind| kind.get_char()), Some('m'));
+		assert_eq!(map.get_entity(2, 1).map(|kind| kind.get_char()), Some('M'));
+		assert_eq!(map.get_entity(2, 3).map(|kind| kind.get_char()), Some('>'));
+		assert_eq!(map.entities.len(), 4);
+
+		assert_eq!(map.to_string(), text);
+		assert!(Map::try_from(map.to_string().as_str()) == Ok(map));
+	}
+
+	#[test]
+	fn bare_map_loads() {
+		let map = Map::try_from("@.#\n..>\n").unwrap_or_else(|_| panic!());
+		assert!(map.header == Header::default());
+		assert!(map.comments.is_empty());
+		assert_eq!(map.cells.get_size(), (2, 3));
+		assert_eq!(map.entities.len(), 2);
+		assert!(map.cells.get(0, 0) == Some(CellKind::Vacant));
+
+		// lines that would be header or comments are rows without the magic line
+		assert!(Map::try_from("// a\n...").is_err());
+		assert!(Map::try_from(map.to_string().as_str()) == Ok(map));
+	}
+
+	#[test]
+	fn player_and_stairs_are_unique() {
+		assert!(Map::try_from("@.@\n..>") == Err(MapError::DuplicateEntity));
+		assert!(Map::try_from(">..\n..>") == Err(MapError::DuplicateEntity));
+
+		let mut map = Map::try_from("@m.\n.m>").unwrap_or_else(|_| panic!());
+		map.set_entity(1, 0, Some(EntityKind::Player));
+		map.set_entity(0, 2, Some(EntityKind::Stairs));
+		map.set_entity(0, 1, Some(EntityKind::Monster));
+		map.set_entity(1, 2, Some(EntityKind::Monster));
+		assert_eq!(map.to_string(), "!shiren6-map v1\n.m>\n@mm\n");
+	}
+}
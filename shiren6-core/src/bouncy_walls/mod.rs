@@ -0,0 +1,12 @@
+pub mod cell;
+pub mod components;
+pub mod diff;
+pub mod escape;
+pub mod export;
+pub mod heading;
+pub mod json;
+pub mod line_of_fire;
+pub mod map;
+#[cfg(test)]
+mod reference;
+pub mod walk;
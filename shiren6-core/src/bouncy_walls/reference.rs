@@ -0,0 +1,157 @@
+// The search `Cells` did before it kept packed masks, on plain `Vec<bool>`s with bounds
+// checks instead of a border. The tests check `Cells` against it and the benchmarks compare
+// the two, so it depends on nothing else in the crate.
+
+pub struct Reference {
+	height: usize,
+	width: usize,
+	solid: Vec<bool>,
+	bouncy: Vec<bool>,
+}
+
+impl Reference {
+	// same as `Cells::BOUNCE_*`
+	const BOUNCE_TOP: u32 = 1;
+	const BOUNCE_LEFT: u32 = 2;
+	const BOUNCE_BOTTOM: u32 = 4;
+	const BOUNCE_RIGHT: u32 = 8;
+	const BOUNCE_ALL: u32 = 15;
+
+	pub fn new(height: usize, width: usize, solid: Vec<bool>, bouncy: Vec<bool>) -> Self {
+		Self {
+			height,
+			width,
+			solid,
+			bouncy,
+		}
+	}
+
+	fn len(&self) -> usize {
+		self.height * self.width
+	}
+
+	pub fn find_routes(&self) -> Vec<usize> {
+		self.components()
+			.into_iter()
+			.filter(|&(_, bounce_flag)| bounce_flag == Self::BOUNCE_ALL)
+			.map(|(v, _)| v)
+			.collect()
+	}
+
+	pub fn components(&self) -> Vec<(usize, u32)> {
+		let mut components = Vec::new();
+		let mut visited = vec![false; self.len() * 2];
+
+		for i in (0..self.len()).filter(|&i| !self.solid[i]) {
+			for v in [i, i + self.len()] {
+				if !visited[v] {
+					let bounce_flag = self.dfs(v, &mut visited, |_| {});
+					components.push((v, bounce_flag));
+				}
+			}
+		}
+
+		components
+	}
+
+	pub fn trace(&self, v: usize) -> Vec<u8> {
+		let mut trace = vec![0; self.len()];
+		let mut visited = vec![false; self.len() * 2];
+
+		self.dfs(v, &mut visited, |v| {
+			trace[v % self.len()] |= 1 << (v / self.len());
+		});
+
+		trace
+	}
+
+	fn dfs(&self, v: usize, visited: &mut [bool], mut f: impl FnMut(usize)) -> u32 {
+		let len = self.len();
+		let mut bounce_flag = 0;
+		let mut stack = vec![v];
+		visited[v] = true;
+
+		let mut visit = |nv: usize, stack: &mut Vec<usize>| {
+			if !visited[nv] {
+				visited[nv] = true;
+				stack.push(nv);
+			}
+		};
+
+		while let Some(v) = stack.pop() {
+			f(v);
+
+			let (layer, y, x) = (v / len, v % len / self.width, v % self.width);
+			let step = |dy: isize, dx: isize| {
+				y.checked_add_signed(dy)
+					.filter(|&y| y < self.height)
+					.zip(x.checked_add_signed(dx).filter(|&x| x < self.width))
+					.map(|(y, x)| y * self.width + x)
+			};
+			let dy = if layer == 0 { [-1, 1] } else { [1, -1] };
+
+			for (dy, dx) in dy.into_iter().zip([-1, 1]) {
+				let Some(n) = step(dy, dx) else {
+					continue;
+				};
+				if !self.solid[n] {
+					visit(layer * len + n, &mut stack);
+					continue;
+				}
+
+				let adj_y = step(dy, 0).unwrap();
+				let adj_x = step(0, dx).unwrap();
+				match (self.solid[adj_y], self.solid[adj_x]) {
+					(true, false) => {
+						if self.bouncy[n] && self.bouncy[adj_y] {
+							bounce_flag |= if dy == -1 {
+								Self::BOUNCE_TOP
+							} else {
+								Self::BOUNCE_BOTTOM
+							};
+						}
+						visit((layer ^ 1) * len + adj_x, &mut stack);
+					}
+					(false, true) => {
+						if self.bouncy[n] && self.bouncy[adj_x] {
+							bounce_flag |= if dx == -1 {
+								Self::BOUNCE_LEFT
+							} else {
+								Self::BOUNCE_RIGHT
+							};
+						}
+						visit((layer ^ 1) * len + adj_y, &mut stack);
+					}
+					_ => {}
+				}
+			}
+		}
+
+		bounce_flag
+	}
+}
+
+/// A map of `height` rows in the textarea format, mostly vacant with scattered walls, bouncy
+/// walls, water and pits.
+pub fn random_map(seed: &mut u64, height: usize, width: usize) -> String {
+	let mut map = String::new();
+	for _ in 0..height {
+		for _ in 0..width {
+			map.push(random_tile(seed));
+		}
+		map.push('\n');
+	}
+	map
+}
+
+pub fn random_tile(seed: &mut u64) -> char {
+	b".....##bbbbw,"[random(seed, 13)] as char
+}
+
+/// xorshift, so that failures reproduce
+pub fn random(seed: &mut u64, n: usize) -> usize {
+	*seed ^= *seed << 13;
+	*seed ^= *seed >> 7;
+	*seed ^= *seed << 17;
+	(*seed % n as u64) as usize
+}
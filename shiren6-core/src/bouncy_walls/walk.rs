@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use super::{
+	cell::{CellKind, Cells},
+	heading::Heading,
+};
+
+/// What the walker may step onto besides vacant cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkRules {
+	pub through_water: bool,
+	pub through_pits: bool,
+}
+
+impl WalkRules {
+	pub fn is_walkable(&self, kind: CellKind) -> bool {
+		use CellKind::*;
+
+		match kind {
+			Vacant => true,
+			Water => self.through_water,
+			Pit => self.through_pits,
+			Wall | BouncyWall => false,
+		}
+	}
+
+	/// The tiles one step away from `(y, x)`. A diagonal step is blocked when either of the
+	/// two tiles it cuts past is solid.
+	pub fn get_steps<'a>(
+		&'a self,
+		cells: &'a Cells,
+		y: usize,
+		x: usize,
+	) -> impl Iterator<Item = (Heading, (usize, usize))> + 'a {
+		let (height, width) = cells.get_size();
+		let kind = move |(y, x): (usize, usize)| cells.get(y, x).unwrap();
+
+		Heading::ALL.into_iter().filter_map(move |heading| {
+			let next = heading.step(y, x, height, width)?;
+			if !self.is_walkable(kind(next)) {
+				return None;
+			}
+			if heading.is_diagonal()
+				&& (kind((next.0, x)).is_solid() || kind((y, next.1)).is_solid())
+			{
+				return None;
+			}
+			Some((heading, next))
+		})
+	}
+
+	/// A shortest walk from `from` to `to`, both ends included, or `None` if there is none.
+	/// Every step takes one turn.
+	pub fn find_path(
+		&self,
+		cells: &Cells,
+		from: (usize, usize),
+		to: (usize, usize),
+	) -> Option<Vec<(usize, usize)>> {
+		let (_, width) = cells.get_size();
+		let index = |(y, x): (usize, usize)| y * width + x;
+
+		let mut previous: Vec<Option<(usize, usize)>> = vec![None; cells.len()];
+		let mut queue = VecDeque::from([from]);
+		previous[index(from)] = Some(from);
+
+		while let Some(tile) = queue.pop_front() {
+			if tile == to {
+				let mut path = vec![to];
+				let mut tile = to;
+				while tile != from {
+					tile = previous[index(tile)].unwrap();
+					path.push(tile);
+				}
+				path.reverse();
+				return Some(path);
+			}
+
+			for (_, next) in self.get_steps(cells, tile.0, tile.1) {
+				if previous[index(next)].is_none() {
+					previous[index(next)] = Some(tile);
+					queue.push_back(next);
+				}
+			}
+		}
+
+		None
+	}
+}
@@ -0,0 +1,4 @@
+//! Game logic behind shiren6-utils, free of any UI or web dependency.
+
+pub mod bouncy_walls;
+pub mod price_list;
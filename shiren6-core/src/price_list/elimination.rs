@@ -0,0 +1,213 @@
+/// Narrows down `candidates`, what each of several unknowns can be, knowing that no two
+/// unknowns are the same.
+///
+/// A value is ruled out for an unknown when taking it would leave the others unable to all be
+/// different, so whenever `k` unknowns can only be among `k` values, those values are removed
+/// from every other unknown, whether or not the `k` share the same candidates. Unknowns without
+/// any candidate are left out.
+///
+/// Returns the unknowns that cannot all be different, e.g. two that can only be the same
+/// value, in which case `candidates` are left as they are. Empty when there is no such
+/// contradiction.
+pub fn eliminate<T: Copy + PartialEq>(candidates: &mut [Vec<T>]) -> Vec<usize> {
+	let mut values: Vec<T> = Vec::new();
+	let edges: Vec<Vec<usize>> = candidates
+		.iter()
+		.map(|list| {
+			list.iter()
+				.map(|value| {
+					values.iter().position(|v| v == value).unwrap_or_else(|| {
+						values.push(*value);
+						values.len() - 1
+					})
+				})
+				.collect()
+		})
+		.collect();
+
+	let mut matching = Matching::new(edges, values.len());
+	for u in 0..candidates.len() {
+		matching.augment(u, &mut vec![false; values.len()]);
+	}
+
+	let unmatched: Vec<usize> = (0..candidates.len())
+		.filter(|&u| !candidates[u].is_empty() && matching.assigned[u].is_none())
+		.collect();
+	if !unmatched.is_empty() {
+		return matching.get_conflicts(unmatched);
+	}
+
+	for (u, list) in candidates.iter_mut().enumerate() {
+		*list = list
+			.iter()
+			.zip(&matching.edges[u])
+			.filter(|&(_, &v)| matching.can_take(u, v))
+			.map(|(value, _)| *value)
+			.collect();
+	}
+	Vec::new()
+}
+
+// a largest set of distinct values given to the unknowns
+struct Matching {
+	// the values each unknown can be
+	edges: Vec<Vec<usize>>,
+	// the value given to each unknown
+	assigned: Vec<Option<usize>>,
+	// the unknown each value is given to
+	owners: Vec<Option<usize>>,
+}
+
+impl Matching {
+	fn new(edges: Vec<Vec<usize>>, values: usize) -> Self {
+		Self {
+			assigned: vec![None; edges.len()],
+			owners: vec![None; values],
+			edges,
+		}
+	}
+
+	// gives `u` a value, moving other unknowns to other values as needed, without touching the
+	// values already `visited`
+	fn augment(&mut self, u: usize, visited: &mut [bool]) -> bool {
+		for i in 0..self.edges[u].len() {
+			let v = self.edges[u][i];
+			if visited[v] {
+				continue;
+			}
+			visited[v] = true;
+			if self.owners[v].is_none_or(|owner| self.augment(owner, visited)) {
+				self.assigned[u] = Some(v);
+				self.owners[v] = Some(u);
+				return true;
+			}
+		}
+		false
+	}
+
+	// whether every unknown can still be given a value when `u` is given `v`
+	fn can_take(&self, u: usize, v: usize) -> bool {
+		let Some(owner) = self.owners[v].filter(|&owner| owner != u) else {
+			return true;
+		};
+
+		let mut matching = Self {
+			edges: self.edges.clone(),
+			assigned: self.assigned.clone(),
+			owners: self.owners.clone(),
+		};
+		if let Some(previous) = matching.assigned[u] {
+			matching.owners[previous] = None;
+		}
+		matching.assigned[u] = Some(v);
+		matching.owners[v] = Some(u);
+		matching.assigned[owner] = None;
+
+		let mut visited = vec![false; self.owners.len()];
+		// keeps `u` on `v`
+		visited[v] = true;
+		matching.augment(owner, &mut visited)
+	}
+
+	// the unknowns `unmatched` share their values with, which together have fewer values than
+	// unknowns
+	fn get_conflicts(&self, unmatched: Vec<usize>) -> Vec<usize> {
+		let mut conflicts = unmatched;
+		let mut i = 0;
+		while i < conflicts.len() {
+			for &v in &self.edges[conflicts[i]] {
+				// every value is given to someone, or `conflicts[i]` would have taken it
+				let owner = self.owners[v].unwrap();
+				if !conflicts.contains(&owner) {
+					conflicts.push(owner);
+				}
+			}
+			i += 1;
+		}
+		conflicts.sort_unstable();
+		conflicts
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pairs_rule_out_values() {
+		let mut candidates = vec![vec![1, 2], vec![1, 2], vec![1, 2, 3]];
+		assert!(eliminate(&mut candidates).is_empty());
+		assert_eq!(candidates, [vec![1, 2], vec![1, 2], vec![3]]);
+	}
+
+	#[test]
+	fn subsets_need_not_be_equal() {
+		let mut candidates = vec![vec![1, 2], vec![2, 3], vec![1, 3], vec![1, 2, 3, 4]];
+		assert!(eliminate(&mut candidates).is_empty());
+		assert_eq!(candidates, [vec![1, 2], vec![2, 3], vec![1, 3], vec![4]]);
+	}
+
+	#[test]
+	fn forced_values_chain() {
+		let mut candidates = vec![vec![], vec![2, 3], vec![1], vec![1, 2]];
+		assert!(eliminate(&mut candidates).is_empty());
+		assert_eq!(candidates, [vec![], vec![3], vec![1], vec![2]]);
+	}
+
+	#[test]
+	fn contradictions_are_reported() {
+		let mut candidates = vec![vec![1, 2], vec![1], vec![3, 4], vec![1]];
+		assert_eq!(eliminate(&mut candidates), [1, 3]);
+		assert_eq!(candidates, [vec![1, 2], vec![1], vec![3, 4], vec![1]]);
+
+		let mut candidates = vec![vec![1, 2], vec![2, 3], vec![1, 3], vec![3, 1], vec![4]];
+		assert_eq!(eliminate(&mut candidates), [0, 1, 2, 3]);
+	}
+
+	// every way of giving each unknown, but for those without candidates, a different value
+	fn assignments(candidates: &[Vec<u8>]) -> Vec<Vec<Option<u8>>> {
+		let Some((last, rest)) = candidates.split_last() else {
+			return vec![Vec::new()];
+		};
+		let mut list = Vec::new();
+		for assignment in assignments(rest) {
+			if last.is_empty() {
+				list.push([assignment.clone(), vec![None]].concat());
+			}
+			for &value in last {
+				if !assignment.contains(&Some(value)) {
+					list.push([assignment.clone(), vec![Some(value)]].concat());
+				}
+			}
+		}
+		list
+	}
+
+	#[test]
+	fn matches_trying_every_assignment() {
+		// each of four unknowns can be any subset of four values
+		for sets in 0..1 << 16 {
+			let mut candidates: Vec<Vec<u8>> = (0..4)
+				.map(|u| (0..4).filter(|v| sets >> (u * 4 + v) & 1 == 1).collect())
+				.collect();
+			let assignments = assignments(&candidates);
+			let expected: Vec<Vec<u8>> = (0..4)
+				.map(|u| {
+					(0..4)
+						.filter(|&v| assignments.iter().any(|a| a[u] == Some(v)))
+						.collect()
+				})
+				.collect();
+
+			let original = candidates.clone();
+			let conflicts = eliminate(&mut candidates);
+			if assignments.is_empty() {
+				assert!(!conflicts.is_empty(), "{original:?}");
+				assert_eq!(candidates, original);
+			} else {
+				assert!(conflicts.is_empty(), "{original:?}");
+				assert_eq!(candidates, expected, "{original:?}");
+			}
+		}
+	}
+}
@@ -0,0 +1,56 @@
+use std::ptr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+	items::{self, Category, Item, Variation},
+	Price,
+};
+
+/// Something seen of an unidentified item.
+///
+/// Prices are taken at one moment, and zaps and contents are counted from then on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Observation {
+	/// a shop sells it for this much
+	Buy(Price),
+	/// the shopkeeper offers this much for it
+	Sell(Price),
+	/// a staff that still worked this many times
+	Zaps(u16),
+	/// a pot that took this many items
+	Holds(u16),
+}
+
+impl Observation {
+	pub fn matches(&self, item: &Item) -> bool {
+		let count = item.count.unwrap_or(0);
+		match *self {
+			Observation::Buy(price) => item.buy == price,
+			Observation::Sell(price) => item.sell == price,
+			Observation::Zaps(zaps) => {
+				matches!(item.category.variation, Variation::Charges) && count >= zaps
+			}
+			Observation::Holds(held) => {
+				matches!(item.category.variation, Variation::Capacity) && count >= held
+			}
+		}
+	}
+}
+
+/// Every item, state and count consistent with all of `observations`, in the order of the
+/// tables.
+pub fn infer(
+	category: Option<&'static Category>,
+	observations: &[Observation],
+) -> Vec<&'static Item> {
+	items::filtered_items(Ok(None))
+		.filter(|item| category.is_none_or(|category| ptr::eq(item.category, category)))
+		.filter(|item| {
+			observations
+				.iter()
+				.all(|observation| observation.matches(item))
+		})
+		.collect()
+}
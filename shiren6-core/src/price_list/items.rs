@@ -0,0 +1,276 @@
+use std::{num::ParseIntError, ptr, sync::OnceLock};
+
+use super::{
+	rules::{PriceRules, StateRule},
+	Price,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ItemState {
+	Normal,
+	Blessed,
+	Cursed,
+}
+
+impl ItemState {
+	pub fn get_state_str(&self) -> &str {
+		use ItemState::*;
+
+		match self {
+			Normal => "",
+			Blessed => "🔔",
+			Cursed => "💀",
+		}
+	}
+}
+
+/// What a row of a table expands into besides its states.
+pub enum Variation {
+	/// a single item
+	None,
+	/// `0..=max` charges of a staff, `max` being the most a row is found with
+	Charges,
+	/// `0..=max` capacity of a pot, the same way
+	Capacity,
+	/// every `+N` a shop may have
+	Modifier,
+	/// stacks of `1..=max_stack`, priced per unit
+	Stack,
+}
+
+/// A row of one of the CSV files, checked by the build script.
+pub struct Row {
+	pub name: &'static str,
+	pub buy: Price,
+	/// otherwise the sell price is derived from the buy price
+	pub sell: Option<Price>,
+	/// the most charges or capacity a staff or pot is found with
+	pub max_count: Option<u16>,
+	/// seal slots of a weapon or shield
+	pub slots: Option<u16>,
+}
+
+/// A table of items and how they are priced.
+pub struct Category {
+	/// the name used by the JavaScript API
+	pub id: &'static str,
+	/// the heading in the list
+	pub name: &'static str,
+	pub rows: &'static [Row],
+	pub rules: PriceRules,
+	pub variation: Variation,
+}
+
+impl Category {
+	/// Headings of the columns between the name and the prices.
+	pub fn get_columns(&self) -> Vec<&'static str> {
+		match self.variation {
+			Variation::None => Vec::new(),
+			Variation::Charges => vec!["回数"],
+			Variation::Capacity => vec!["容量"],
+			Variation::Stack => vec!["本数"],
+			Variation::Modifier => vec!["修正値", "印"],
+		}
+	}
+}
+
+// `pub static CATEGORIES: [Category; _]`, one per line of categories.csv in its order
+include!(concat!(env!("OUT_DIR"), "/categories.rs"));
+
+#[derive(Clone, Copy)]
+pub struct Item {
+	pub category: &'static Category,
+	pub name: &'static str,
+	pub state: ItemState,
+	pub buy: Price,
+	pub sell: Price,
+	/// charges of a staff, capacity of a pot or size of a stack
+	pub count: Option<u16>,
+	/// `+N` of a weapon or shield
+	pub modifier: Option<i16>,
+	/// seal slots of a weapon or shield
+	pub slots: Option<u16>,
+}
+
+impl Item {
+	/// Values of the columns given by `Category::get_columns`.
+	pub fn get_columns(&self) -> Vec<String> {
+		self.count
+			.map(|count| count.to_string())
+			.into_iter()
+			.chain(self.modifier.map(|modifier| format!("{modifier:+}")))
+			.chain(self.slots.map(|slots| slots.to_string()))
+			.collect()
+	}
+}
+
+fn expand(category: &'static Category, list: &mut Vec<Item>) {
+	let rules = &category.rules;
+	for row in category.rows {
+		let Row {
+			name,
+			buy,
+			sell,
+			max_count,
+			slots,
+		} = *row;
+		let sell = sell.unwrap_or_else(|| rules.get_sell(buy));
+
+		let item = Item {
+			category,
+			name,
+			state: ItemState::Normal,
+			buy,
+			sell,
+			count: None,
+			modifier: None,
+			slots: None,
+		};
+		// the items before the state is applied
+		let variants: Vec<_> = match category.variation {
+			Variation::None => vec![item],
+			Variation::Charges | Variation::Capacity => (0..=max_count.unwrap())
+				.map(|count| {
+					let buy = rules.get_count_price(buy, count);
+					Item {
+						buy,
+						sell: rules.get_sell(buy),
+						count: Some(count),
+						..item
+					}
+				})
+				.collect(),
+			Variation::Modifier => (rules.min_modifier..=rules.max_modifier)
+				.map(|modifier| {
+					let buy = rules.get_modified_price(buy, modifier);
+					Item {
+						buy,
+						sell: rules.get_sell(buy),
+						modifier: Some(modifier),
+						slots,
+						..item
+					}
+				})
+				.collect(),
+			Variation::Stack => (1..=rules.max_stack)
+				.map(|count| Item {
+					buy: buy.saturating_mul(count as Price),
+					sell: sell.saturating_mul(count as Price),
+					count: Some(count),
+					..item
+				})
+				.collect(),
+		};
+
+		for item in variants {
+			for rule in rules.states {
+				let (buy, sell) = rule.apply(item.buy, item.sell);
+				list.push(Item {
+					state: rule.state,
+					buy,
+					sell,
+					..item
+				});
+			}
+		}
+	}
+}
+
+/// The category whose `id` is `id`.
+pub fn get_category(id: &str) -> Option<&'static Category> {
+	CATEGORIES.iter().find(|category| category.id == id)
+}
+
+/// Every item of every category, category by category.
+fn items() -> &'static Vec<Item> {
+	static ITEMS: OnceLock<Vec<Item>> = OnceLock::new();
+	ITEMS.get_or_init(|| {
+		let mut list = Vec::new();
+		for category in CATEGORIES.iter() {
+			expand(category, &mut list);
+		}
+		list
+	})
+}
+
+/// Items of every category whose buy or sell price matches, or all of them without a price.
+pub fn filtered_items(
+	price: Result<Option<Price>, ParseIntError>,
+) -> impl Iterator<Item = &'static Item> {
+	items().iter().filter(move |item| {
+		price
+			.as_ref()
+			.is_ok_and(|price| price.is_none_or(|price| item.buy == price || item.sell == price))
+	})
+}
+
+pub fn filtered_category(
+	category: &'static Category,
+	price: Result<Option<Price>, ParseIntError>,
+) -> impl Iterator<Item = &'static Item> {
+	filtered_items(price).filter(move |item| ptr::eq(item.category, category))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// (name, state, buy, sell, count) as the tables were priced before the rules were data
+	type Priced = (&'static str, ItemState, Price, Price, Option<u16>);
+
+	fn cursed(price: Price) -> Price {
+		price * 87 / 100
+	}
+
+	fn old_prices(csv: &'static str, blessed: bool, counted: bool) -> Vec<Priced> {
+		let mut list = Vec::new();
+		for line in csv.trim().lines() {
+			let fields: Vec<&str> = line.split(',').collect();
+			let name = fields[0];
+			let buy: Price = fields[1].parse().unwrap();
+			if counted {
+				let max: u16 = fields[2].parse().unwrap();
+				for count in 0..=max {
+					let buy = buy + 100 * count as Price;
+					let sell = buy * 2 / 5;
+					list.push((name, ItemState::Normal, buy, sell, Some(count)));
+					list.push((
+						name,
+						ItemState::Cursed,
+						cursed(buy),
+						cursed(sell),
+						Some(count),
+					));
+				}
+			} else {
+				let sell = fields
+					.get(2)
+					.map_or(buy * 2 / 5, |sell| sell.parse().unwrap());
+				list.push((name, ItemState::Normal, buy, sell, None));
+				if blessed {
+					list.push((name, ItemState::Blessed, buy * 2, sell * 2, None));
+				}
+				list.push((name, ItemState::Cursed, cursed(buy), cursed(sell), None));
+			}
+		}
+		list
+	}
+
+	#[test]
+	fn original_tables_keep_their_prices() {
+		let tables = [
+			("bracelet", include_str!("./bracelets.csv"), false, false),
+			("grass", include_str!("./grasses.csv"), true, false),
+			("scroll", include_str!("./scrolls.csv"), true, false),
+			("staff", include_str!("./staves.csv"), false, true),
+			("pot", include_str!("./pots.csv"), false, true),
+		];
+		for (id, csv, blessed, counted) in tables {
+			let category = get_category(id).unwrap();
+			let prices: Vec<Priced> = filtered_category(category, Ok(None))
+				.map(|item| (item.name, item.state, item.buy, item.sell, item.count))
+				.collect();
+			assert!(prices == old_prices(csv, blessed, counted), "{id}");
+		}
+	}
+}
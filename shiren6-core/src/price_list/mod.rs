@@ -0,0 +1,8 @@
+pub mod elimination;
+pub mod inference;
+pub mod items;
+pub mod notebook;
+pub mod rules;
+
+/// A price in gitan, wide enough for anything a shop asks.
+pub type Price = u32;
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+	elimination,
+	inference::{self, Observation},
+	items,
+};
+
+/// An item that appears under a random name for the whole run, e.g. a red grass.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+	/// `Category::id`
+	pub category: String,
+	/// the name it appears under
+	pub alias: String,
+	#[serde(default)]
+	pub observations: Vec<Observation>,
+	#[serde(default)]
+	pub note: String,
+	/// the name of the item once identified
+	#[serde(default)]
+	pub identified: Option<String>,
+}
+
+impl Entry {
+	pub fn new(category: &str, alias: &str) -> Self {
+		Self {
+			category: String::from(category),
+			alias: String::from(alias),
+			observations: Vec::new(),
+			note: String::new(),
+			identified: None,
+		}
+	}
+}
+
+/// What the entries of a notebook can be, taken together.
+pub struct Solution {
+	/// the names each entry can still be
+	pub candidates: Vec<Vec<&'static str>>,
+	/// entries that cannot all be different items, e.g. two that can only be the same, in
+	/// which case `candidates` are what each can be by itself
+	pub conflicts: Vec<usize>,
+}
+
+/// The unidentified items of one run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notebook {
+	pub entries: Vec<Entry>,
+}
+
+impl Notebook {
+	/// Whether an entry other than `index` was identified as `name` in the same category.
+	fn is_identified_elsewhere(&self, index: usize, name: &str) -> bool {
+		let category = &self.entries[index].category;
+		self.entries.iter().enumerate().any(|(i, entry)| {
+			i != index && entry.category == *category && entry.identified.as_deref() == Some(name)
+		})
+	}
+
+	/// The distinct names entry `index` can be by its own observations, in the order of the
+	/// table, leaving out the names other entries were identified as.
+	fn get_names(&self, index: usize) -> Vec<&'static str> {
+		let entry = &self.entries[index];
+		let Some(category) = items::get_category(&entry.category) else {
+			return Vec::new();
+		};
+
+		let mut names = Vec::new();
+		for item in inference::infer(Some(category), &entry.observations) {
+			let identified = entry
+				.identified
+				.as_deref()
+				.is_none_or(|name| item.name == name);
+			if identified
+				&& !names.contains(&item.name)
+				&& !self.is_identified_elsewhere(index, item.name)
+			{
+				names.push(item.name);
+			}
+		}
+		names
+	}
+
+	/// The names each entry can still be once the items other entries are, or must be, are
+	/// ruled out.
+	pub fn solve(&self) -> Solution {
+		let mut candidates: Vec<_> = (0..self.entries.len())
+			.map(|index| self.get_names(index))
+			.collect();
+		// names are unique across the tables, so the categories need not be kept apart
+		let conflicts = elimination::eliminate(&mut candidates);
+		Solution {
+			candidates,
+			conflicts,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(alias: &str, observations: &[Observation]) -> Entry {
+		Entry {
+			observations: observations.to_vec(),
+			..Entry::new("grass", alias)
+		}
+	}
+
+	#[test]
+	fn two_entries_forced_to_one_item_conflict() {
+		let notebook = Notebook {
+			entries: vec![
+				entry("赤い草", &[Observation::Buy(40)]),
+				entry("青い草", &[]),
+				entry("黄色い草", &[Observation::Buy(40)]),
+			],
+		};
+		let solution = notebook.solve();
+		assert_eq!(solution.conflicts, [0, 2]);
+		assert_eq!(solution.candidates[0], ["薬草"]);
+		assert_eq!(solution.candidates[2], ["薬草"]);
+	}
+
+	#[test]
+	fn identified_names_are_ruled_out_elsewhere() {
+		let notebook = Notebook {
+			entries: vec![
+				// identified against its own observation, which leaves it no candidate
+				Entry {
+					identified: Some(String::from("薬草")),
+					..entry("赤い草", &[Observation::Buy(10)])
+				},
+				entry("青い草", &[]),
+			],
+		};
+		let solution = notebook.solve();
+		assert!(solution.candidates[0].is_empty());
+		assert!(!solution.candidates[1].contains(&"薬草"));
+	}
+
+	#[test]
+	fn forced_items_are_ruled_out_elsewhere() {
+		let notebook = Notebook {
+			entries: vec![
+				entry("赤い草", &[Observation::Buy(40)]),
+				entry("青い草", &[]),
+			],
+		};
+		let solution = notebook.solve();
+		assert!(solution.conflicts.is_empty());
+		assert!(!solution.candidates[1].contains(&"薬草"));
+		assert!(solution.candidates[1].contains(&"雑草"));
+	}
+}
@@ -0,0 +1,200 @@
+use super::{items::ItemState, Price};
+
+/// How a scaled price is made a whole number again.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+	Down,
+	Nearest,
+	Up,
+}
+
+impl Rounding {
+	fn divide(self, value: i128, divisor: i128) -> i128 {
+		match self {
+			Rounding::Down => value.div_euclid(divisor),
+			Rounding::Nearest => (value * 2 + divisor).div_euclid(divisor * 2),
+			Rounding::Up => -(-value).div_euclid(divisor),
+		}
+	}
+}
+
+// prices that do not fit are capped rather than wrapped around
+fn to_price(value: i128) -> Price {
+	value.clamp(0, Price::MAX as i128) as Price
+}
+
+/// Multiplies a price by `numerator / denominator`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+	pub numerator: u32,
+	pub denominator: u32,
+	pub rounding: Rounding,
+}
+
+impl Ratio {
+	pub const ONE: Ratio = Ratio::new(1, 1);
+
+	/// Rounds down, as the game does unless stated otherwise.
+	pub const fn new(numerator: u32, denominator: u32) -> Self {
+		Self {
+			numerator,
+			denominator,
+			rounding: Rounding::Down,
+		}
+	}
+
+	pub const fn rounded(self, rounding: Rounding) -> Self {
+		Self { rounding, ..self }
+	}
+
+	pub fn apply(&self, price: Price) -> Price {
+		let value = price as i128 * self.numerator as i128;
+		to_price(self.rounding.divide(value, self.denominator as i128))
+	}
+}
+
+/// How the prices of an item in a state relate to those of a normal one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct StateRule {
+	pub state: ItemState,
+	pub buy: Ratio,
+	pub sell: Ratio,
+}
+
+impl StateRule {
+	pub const NORMAL: StateRule = StateRule {
+		state: ItemState::Normal,
+		buy: Ratio::ONE,
+		sell: Ratio::ONE,
+	};
+	pub const BLESSED: StateRule = StateRule {
+		state: ItemState::Blessed,
+		buy: Ratio::new(2, 1),
+		sell: Ratio::new(2, 1),
+	};
+	pub const CURSED: StateRule = StateRule {
+		state: ItemState::Cursed,
+		buy: Ratio::new(87, 100),
+		sell: Ratio::new(87, 100),
+	};
+
+	/// The buy and sell prices in this state of an item whose normal prices are `buy` and
+	/// `sell`.
+	pub fn apply(&self, buy: Price, sell: Price) -> (Price, Price) {
+		(self.buy.apply(buy), self.sell.apply(sell))
+	}
+}
+
+/// How the rows of a table are turned into prices.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PriceRules {
+	/// the states the items are found in, in the order they are listed
+	pub states: &'static [StateRule],
+	/// the sell price from the buy price, when the table has no sell column or the buy price
+	/// depends on the count or `+N`
+	pub sell: Ratio,
+	/// added to the buy price per charge of a staff or per capacity of a pot
+	pub per_unit: Price,
+	/// the part of the base buy price added per `+1` and taken off per `-1`
+	pub per_modifier: Ratio,
+	/// the lowest `+N` a weapon or shield in a shop is assumed to have
+	pub min_modifier: i16,
+	/// the highest `+N` a weapon or shield in a shop is assumed to have
+	pub max_modifier: i16,
+	/// the largest stack of arrows or stones listed
+	pub max_stack: u16,
+}
+
+impl PriceRules {
+	pub const DEFAULT: PriceRules = PriceRules {
+		states: &[StateRule::NORMAL, StateRule::CURSED],
+		sell: Ratio::new(2, 5),
+		per_unit: 100,
+		per_modifier: Ratio::new(1, 10),
+		min_modifier: -3,
+		max_modifier: 5,
+		max_stack: 30,
+	};
+
+	pub fn get_sell(&self, buy: Price) -> Price {
+		self.sell.apply(buy)
+	}
+
+	/// The buy price with `count` charges or capacity.
+	pub fn get_count_price(&self, buy: Price, count: u16) -> Price {
+		buy.saturating_add(self.per_unit.saturating_mul(count as Price))
+	}
+
+	/// The buy price at `+modifier`.
+	pub fn get_modified_price(&self, buy: Price, modifier: i16) -> Price {
+		let Ratio {
+			numerator,
+			denominator,
+			rounding,
+		} = self.per_modifier;
+		let value = buy as i128 * (denominator as i128 + modifier as i128 * numerator as i128);
+		to_price(rounding.divide(value, denominator as i128))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn divide_rounds_negative_values_too() {
+		let cases = [
+			(7, [3, 4, 4]),
+			(6, [3, 3, 3]),
+			(5, [2, 3, 3]),
+			(-5, [-3, -2, -2]),
+			(-7, [-4, -3, -3]),
+		];
+		for (value, expected) in cases {
+			let rounded = [Rounding::Down, Rounding::Nearest, Rounding::Up]
+				.map(|rounding| rounding.divide(value, 2));
+			assert_eq!(rounded, expected, "{value} / 2");
+		}
+		assert_eq!(Rounding::Nearest.divide(4, 3), 1);
+		assert_eq!(Rounding::Nearest.divide(5, 3), 2);
+	}
+
+	#[test]
+	fn ratio_applies_rounding_and_caps() {
+		assert_eq!(Ratio::new(2, 5).apply(1250), 500);
+		assert_eq!(Ratio::new(2, 5).apply(1249), 499);
+		assert_eq!(Ratio::new(2, 5).rounded(Rounding::Nearest).apply(1249), 500);
+		assert_eq!(Ratio::new(2, 5).rounded(Rounding::Up).apply(1246), 499);
+		assert_eq!(Ratio::new(87, 100).apply(1000), 870);
+		assert_eq!(Ratio::new(87, 100).apply(99), 86);
+		assert_eq!(Ratio::ONE.apply(Price::MAX), Price::MAX);
+		assert_eq!(Ratio::new(2, 1).apply(Price::MAX), Price::MAX);
+	}
+
+	#[test]
+	fn modified_price_per_step() {
+		let rules = PriceRules::DEFAULT;
+		assert_eq!(rules.get_modified_price(1000, 0), 1000);
+		assert_eq!(rules.get_modified_price(1000, 3), 1300);
+		assert_eq!(rules.get_modified_price(1000, -3), 700);
+		assert_eq!(rules.get_modified_price(1234, 1), 1357);
+		assert_eq!(rules.get_modified_price(1234, -1), 1110);
+		// a price is never negative however low the `+N`
+		assert_eq!(rules.get_modified_price(1000, -20), 0);
+
+		let rules = PriceRules {
+			per_modifier: Ratio::new(1, 10).rounded(Rounding::Up),
+			..PriceRules::DEFAULT
+		};
+		assert_eq!(rules.get_modified_price(1234, 1), 1358);
+		assert_eq!(rules.get_modified_price(1234, -1), 1111);
+	}
+
+	#[test]
+	fn count_price_saturates() {
+		let rules = PriceRules::DEFAULT;
+		assert_eq!(rules.get_count_price(800, 0), 800);
+		assert_eq!(rules.get_count_price(800, 5), 1300);
+		assert_eq!(rules.get_count_price(Price::MAX - 50, 1), Price::MAX);
+	}
+}
@@ -0,0 +1,208 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+use shiren6_core::bouncy_walls::{
+	cell::Cells,
+	diff::{self, RouteChange, RouteChangeKind},
+	map::{Map, MapResult},
+};
+use web_sys::HtmlTextAreaElement;
+
+use super::{View, INITIAL_CELLS};
+
+fn get_kind_str(kind: RouteChangeKind) -> &'static str {
+	use RouteChangeKind::*;
+
+	match kind {
+		Unchanged => "変化なし",
+		Changed => "変化",
+		Appeared => "出現",
+		Disappeared => "消滅",
+		Merged => "合流",
+		Split => "分裂",
+	}
+}
+
+// the tiles whose kind or entity differs
+fn get_changes(before: &Map, after: &Map, changes: &[(usize, usize)]) -> Vec<(usize, usize)> {
+	let (height, width) = after.cells.get_size();
+	(0..height * width)
+		.map(|i| (i / width, i % width))
+		.filter(|&(y, x)| {
+			changes.contains(&(y, x)) || before.get_entity(y, x) != after.get_entity(y, x)
+		})
+		.collect()
+}
+
+// the traces of several paths in one
+fn trace_all(cells: &Cells, representatives: &[usize]) -> Option<Vec<u8>> {
+	representatives
+		.iter()
+		.map(|&v| cells.trace(v))
+		.reduce(|a, b| a.into_iter().zip(b).map(|(a, b)| a | b).collect())
+}
+
+// the numbers shown in the search page, `#1` for the first of `routes`
+fn get_numbers(routes: &[usize], representatives: &[usize]) -> String {
+	representatives
+		.iter()
+		.filter_map(|v| routes.iter().position(|u| u == v))
+		.map(|i| format!("#{}", i + 1))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+struct Comparison {
+	/// the tiles to highlight
+	changes: Rc<Vec<(usize, usize)>>,
+	routes: Vec<RouteChange>,
+	/// what `get_numbers` gives for each of `routes`, before and after
+	numbers: Vec<(String, String)>,
+}
+
+// or `None` when the sizes differ
+fn compare(before: &Map, after: &Map) -> Option<Comparison> {
+	let diff = diff::diff(&before.cells, &after.cells)?;
+	let before_routes = before.cells.find_routes();
+	let after_routes = after.cells.find_routes();
+	let numbers = diff
+		.routes
+		.iter()
+		.map(|change| {
+			(
+				get_numbers(&before_routes, &change.before),
+				get_numbers(&after_routes, &change.after),
+			)
+		})
+		.collect();
+
+	Some(Comparison {
+		changes: Rc::new(get_changes(before, after, &diff.changes)),
+		routes: diff.routes,
+		numbers,
+	})
+}
+
+#[derive(PartialEq, Properties)]
+struct MapInputProperties {
+	label: AttrValue,
+	map: UseStateHandle<MapResult>,
+	onchange: Callback<()>,
+}
+
+#[function_component(MapInput)]
+fn map_input(props: &MapInputProperties) -> Html {
+	let oninput = {
+		let map = props.map.clone();
+		let onchange = props.onchange.clone();
+
+		Callback::from(move |e: InputEvent| {
+			if let Some(textarea) = e.target_dyn_into::<HtmlTextAreaElement>() {
+				map.set(Map::try_from(textarea.value().as_str()));
+				onchange.emit(());
+			}
+		})
+	};
+
+	let textarea_class = props.map.as_ref().map_or("error", |_| "");
+
+	html! {
+		<label>
+			<div>{props.label.clone()}</div>
+			<textarea class={textarea_class} rows={10} oninput={oninput} spellcheck="false" value={INITIAL_CELLS}/>
+		</label>
+	}
+}
+
+#[function_component(BouncyWallsDiff)]
+pub fn bouncy_walls_diff() -> Html {
+	let before: UseStateHandle<MapResult> = use_state(|| TryFrom::try_from(INITIAL_CELLS));
+	let after: UseStateHandle<MapResult> = use_state(|| TryFrom::try_from(INITIAL_CELLS));
+	let before_path = use_state(|| None);
+	let after_path = use_state(|| None);
+	let selected = use_state(|| None::<usize>);
+
+	let onchange = {
+		let before_path = before_path.clone();
+		let after_path = after_path.clone();
+		let selected = selected.clone();
+
+		Callback::from(move |_| {
+			before_path.set(None);
+			after_path.set(None);
+			selected.set(None);
+		})
+	};
+
+	// kept across the renders selecting a row, redone only when either map changes
+	let comparison = use_memo((before.clone(), after.clone()), |(before, after)| {
+		let (Ok(before), Ok(after)) = (before.as_ref(), after.as_ref()) else {
+			return None;
+		};
+		Some(compare(before, after))
+	});
+	let (changes, routes, numbers) = match comparison.as_ref() {
+		Some(Some(comparison)) => (
+			comparison.changes.clone(),
+			comparison.routes.as_slice(),
+			comparison.numbers.as_slice(),
+		),
+		_ => (Rc::default(), &[][..], &[][..]),
+	};
+
+	let onclick = |i: usize, change: &RouteChange| {
+		let before_handle = before.clone();
+		let after_handle = after.clone();
+		let before_path = before_path.clone();
+		let after_path = after_path.clone();
+		let selected = selected.clone();
+		let RouteChange { before, after, .. } = change.clone();
+
+		Callback::from(move |_| {
+			if let (Ok(before_map), Ok(after_map)) = (before_handle.as_ref(), after_handle.as_ref())
+			{
+				before_path.set(trace_all(&before_map.cells, &before));
+				after_path.set(trace_all(&after_map.cells, &after));
+				selected.set(Some(i));
+			}
+		})
+	};
+
+	html! {
+		<div class="bouncy_walls diff">
+			<div class="input_area">
+				<MapInput label="変更前" map={before.clone()} onchange={onchange.clone()}/>
+				<MapInput label="変更後" map={after.clone()} onchange={onchange}/>
+			</div>
+			if let Some(None) = comparison.as_ref() {
+				<div class="error">{"大きさの違うマップは比較できません"}</div>
+			}
+			<div class="views">
+				<View map={before.clone()} path={before_path.clone()} changes={changes.clone()}/>
+				<View map={after.clone()} path={after_path.clone()} changes={changes.clone()}/>
+			</div>
+			if !routes.is_empty() {
+				<table class="route_changes">
+					<thead>
+						<tr>
+							<th scope="col">{"変化"}</th>
+							<th scope="col">{"変更前"}</th>
+							<th scope="col">{"変更後"}</th>
+						</tr>
+					</thead>
+					<tbody>
+					{routes.iter().zip(numbers).enumerate().map(|(i, (change, (before_numbers, after_numbers)))| html! {
+						<tr class={classes!((*selected == Some(i)).then_some("selected"))} onclick={onclick(i, change)}>
+							<td>{get_kind_str(change.kind)}</td>
+							<td>{before_numbers}</td>
+							<td>{after_numbers}</td>
+						</tr>
+					}).collect::<Html>()}
+					</tbody>
+				</table>
+			}
+			<div>{"変化したマス: "}{changes.len()}</div>
+		</div>
+	}
+}
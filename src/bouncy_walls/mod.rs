@@ -1,24 +1,62 @@
-mod cell;
+mod diff;
+mod overlay;
+mod worker;
+
+use std::rc::Rc;
 
 use yew::prelude::*;
 
-use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use shiren6_core::bouncy_walls::{
+	cell::CellKind,
+	escape::plan_escape,
+	export::{self, Direction, Marker},
+	json,
+	map::{EntityKind, Map, MapResult},
+};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+
+use crate::download::download;
+use overlay::{Overlay, OverlayArea};
+use worker::use_analysis;
 
-use cell::{CellKind, CellsResult};
+pub use diff::BouncyWallsDiff;
+pub use worker::AnalysisWorker;
+
+/// An annotation drawn over a tile.
+#[derive(Clone, PartialEq)]
+struct Label {
+	text: AttrValue,
+	title: AttrValue,
+	class: &'static str,
+}
 
 #[derive(PartialEq, Properties)]
 struct CellProperties {
-	cells: UseStateHandle<CellsResult>,
+	map: UseStateHandle<MapResult>,
 	y: usize,
 	x: usize,
 	path: UseStateHandle<Option<Vec<u8>>>,
+	#[prop_or_default]
+	changed: bool,
+	#[prop_or_default]
+	onclick: Option<Callback<MouseEvent>>,
+	#[prop_or_default]
+	label: Option<Label>,
+	/// leaves out the player and monsters, which the labels draw instead
+	#[prop_or_default]
+	hide_pieces: bool,
 }
 
 #[function_component(Cell)]
 fn cell_panel(props: &CellProperties) -> Html {
 	let CellProperties { x, y, path, .. } = props;
-	let cells = props.cells.as_ref().unwrap();
+	let map = props.map.as_ref().unwrap();
+	let cells = &map.cells;
 	let cell = cells.get(*y, *x).expect("Could not obtain the cell kind.");
+	let entity = map
+		.get_entity(*y, *x)
+		.filter(|&kind| !props.hide_pieces || matches!(kind, EntityKind::Stairs));
 
 	let (_, width) = cells.get_size();
 	// mathematically `path == 3` should not happen
@@ -26,49 +64,93 @@ fn cell_panel(props: &CellProperties) -> Html {
 
 	html! {
 		<>
-			<div class={classes!("cell", cell.get_bg_class_name())} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)}>
+			<div class={classes!("cell", cell.get_bg_class_name(), props.changed.then_some("changed"))} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)}>
 			if cell == CellKind::BouncyWall {
 				<div class="cell_inner">
 				</div>
 			}
 			</div>
-			<div class={classes!("path", ["", "diagonal1", "diagonal2"][path])} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)}>
+			<div class={classes!("path", ["", "diagonal1", "diagonal2"][path])} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)} onclick={props.onclick.clone()}>
 			</div>
+			if let Some(entity) = entity {
+				<div class={classes!("entity", entity.get_class_name())} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)} onclick={props.onclick.clone()}>
+					{entity.get_char()}
+				</div>
+			}
+			if let Some(Label { text, title, class }) = props.label.clone() {
+				<div class={classes!("label", class)} title={title} style={format!("grid-row: {}; grid-column: {};", props.y + 1, props.x + 1)} onclick={props.onclick.clone()}>
+					{text}
+				</div>
+			}
 		</>
 	}
 }
 
 #[derive(PartialEq, Properties)]
 struct ViewProperties {
-	cells: UseStateHandle<CellsResult>,
+	map: UseStateHandle<MapResult>,
 	path: UseStateHandle<Option<Vec<u8>>>,
+	/// `(y, x)` of the tiles to highlight
+	#[prop_or_default]
+	changes: Rc<Vec<(usize, usize)>>,
+	/// Shows the cursor and makes the view focusable when set.
+	#[prop_or_default]
+	cursor: Option<UseStateHandle<(usize, usize)>>,
+	#[prop_or_default]
+	onkeydown: Option<Callback<KeyboardEvent>>,
+	/// one per tile in row-major order, or empty
+	#[prop_or_default]
+	labels: Rc<Vec<Option<Label>>>,
+	#[prop_or_default]
+	hide_pieces: bool,
 }
 
 #[function_component(View)]
 fn view(props: &ViewProperties) -> Html {
-	let cells = props.cells.clone();
-	let (height, width) = cells.as_ref().map_or((0, 0), |cells| cells.get_size());
+	let map = props.map.clone();
+	let (height, width) = map.as_ref().map_or((0, 0), |map| map.cells.get_size());
+
+	let onclick = |y: usize, x: usize| {
+		props
+			.cursor
+			.clone()
+			.map(|cursor| Callback::from(move |_| cursor.set((y, x))))
+	};
+	let cursor = props
+		.cursor
+		.as_ref()
+		.map(|cursor| **cursor)
+		.filter(|&(y, x)| y < height && x < width);
 
 	html! {
-		<div class="view">
+		<div class="view" tabindex={props.cursor.is_some().then_some("0")} onkeydown={props.onkeydown.clone()}>
 		{
 			(0..height * width)
 			.map(|i| {
+				let (y, x) = (i / width, i % width);
 				html! {
-					<Cell cells={cells.clone()} y={i / width} x={i % width} path={props.path.clone()}/>
+					<Cell map={map.clone()} y={y} x={x} path={props.path.clone()} changed={props.changes.contains(&(y, x))} onclick={onclick(y, x)} label={props.labels.get(i).cloned().flatten()} hide_pieces={props.hide_pieces}/>
 				}
 			})
 			.collect::<Html>()
 		}
+		if let Some((y, x)) = cursor {
+			<div class="cursor" style={format!("grid-row: {}; grid-column: {};", y + 1, x + 1)}>
+			</div>
+		}
 		</div>
 	}
 }
 
 #[derive(PartialEq, Properties)]
 struct InputAreaProperties {
-	cells: UseStateHandle<CellsResult>,
+	map: UseStateHandle<MapResult>,
+	text: AttrValue,
+	set_text: Callback<String>,
 	representatives: UseStateHandle<Vec<usize>>,
+	busy: bool,
 	path: UseStateHandle<Option<Vec<u8>>>,
+	selected: UseStateHandle<Option<usize>>,
 }
 
 #[function_component(InputArea)]
@@ -76,39 +158,67 @@ fn input_area(props: &InputAreaProperties) -> Html {
 	let textarea_ref = use_node_ref();
 	let select_ref = use_node_ref();
 
-	let textarea_value = use_state(|| AttrValue::from(INITIAL_CELLS));
+	let import_error = use_state(|| false);
+
+	let set_text = props.set_text.clone();
+
+	{
+		let select_ref = select_ref.clone();
+
+		use_effect_with(props.text.clone(), move |_| {
+			if let Some(select) = select_ref.cast::<HtmlSelectElement>() {
+				select.set_value("placeholder");
+			}
+		});
+	}
 
 	let oninput = {
 		let textarea_ref = textarea_ref.clone();
-		let select_ref = select_ref.clone();
-		let textarea_value = textarea_value.clone();
-		let cells_handle = props.cells.clone();
-		let representatives_handle = props.representatives.clone();
-		let path = props.path.clone();
+		let set_text = set_text.clone();
 
 		Callback::from(move |_| {
-			let textarea = textarea_ref.cast::<HtmlTextAreaElement>();
-			let select = select_ref.cast::<HtmlSelectElement>();
-
-			if let Some((textarea, select)) = textarea.zip(select) {
-				let value = textarea.value();
-				let cells: CellsResult = TryFrom::try_from(value.as_ref());
-				if let Ok(cells) = cells.as_ref() {
-					representatives_handle.set(cells.find_routes());
-					select.set_value("placeholder");
-				}
-				cells_handle.set(cells);
-				textarea_value.set(AttrValue::from(value));
-				path.set(None);
+			if let Some(textarea) = textarea_ref.cast::<HtmlTextAreaElement>() {
+				set_text.emit(textarea.value());
 			}
 		})
 	};
 
+	let onchange_import = {
+		let import_error = import_error.clone();
+
+		Callback::from(move |e: Event| {
+			let Some(file) = e
+				.target_dyn_into::<HtmlInputElement>()
+				.and_then(|input| input.files())
+				.and_then(|files| files.get(0))
+			else {
+				return;
+			};
+			let set_text = set_text.clone();
+			let import_error = import_error.clone();
+
+			wasm_bindgen_futures::spawn_local(async move {
+				let text = JsFuture::from(file.text())
+					.await
+					.ok()
+					.and_then(|text| text.as_string());
+				match text.as_deref().map(json::import) {
+					Some(Ok(map)) => {
+						set_text.emit(map.to_string());
+						import_error.set(false);
+					}
+					_ => import_error.set(true),
+				}
+			});
+		})
+	};
+
 	let onchange = {
 		let node_ref = select_ref.clone();
-		let cells_handle = props.cells.clone();
+		let map_handle = props.map.clone();
 		let representatives_handle = props.representatives.clone();
 		let path_handle = props.path.clone();
+		let selected_handle = props.selected.clone();
 
 		Callback::from(move |_| {
 			let select = node_ref.cast::<HtmlSelectElement>();
@@ -116,25 +226,38 @@ fn input_area(props: &InputAreaProperties) -> Html {
 			if let Some(select) = select {
 				let index: usize = select.value().parse().unwrap();
 				let v = representatives_handle[index];
-				path_handle.set(cells_handle.as_ref().unwrap().trace(v).into());
+				path_handle.set(map_handle.as_ref().unwrap().cells.trace(v).into());
+				selected_handle.set(Some(v));
 			}
 		})
 	};
 
-	let textarea_class = props.cells.as_ref().map_or("error", |_| "");
+	let textarea_class = props.map.as_ref().map_or("error", |_| "");
 
 	html! {
 		<div class="input_area">
-			<textarea class={textarea_class} ref={textarea_ref} rows={10} columns={32} oninput={oninput} spellcheck="false" value={(*textarea_value).clone()}/>
-			<div>{"見つかったパス: "}{props.representatives.len()}</div>
-			<select ref={select_ref} onchange={onchange} disabled={props.representatives.len() == 0}>
-			if props.representatives.len() > 0 {
+			<textarea class={textarea_class} ref={textarea_ref} rows={10} columns={32} oninput={oninput} spellcheck="false" value={props.text.clone()}/>
+			<div class="import">
+				<label>{"JSON読込: "}<input type="file" accept=".json,application/json" onchange={onchange_import}/></label>
+				if *import_error {
+					<span class="error">{"読み込めませんでした"}</span>
+				}
+			</div>
+			<div>
+				{"見つかったパス: "}{props.representatives.len()}
+				if props.busy {
+					<span class="busy">{"計算中…"}</span>
+				}
+			</div>
+			<select ref={select_ref} onchange={onchange} disabled={props.busy || props.representatives.is_empty()}>
+			if !props.representatives.is_empty() {
 				<option hidden=true value="placeholder" selected={true}>{"選択してください"}</option>
 				{(0..props.representatives.len()).map(|i| html! {
 					<option value={format!("{i}")}>{'#'}{i + 1}</option>
 				}).collect::<Html>()}
 			}
 			</select>
+			<ExportArea map={props.map.clone()} path={props.path.clone()} selected={props.selected.clone()}/>
 			<table>
 				<thead>
 					<tr>
@@ -176,12 +299,173 @@ fn input_area(props: &InputAreaProperties) -> Html {
 						</th>
 						<td>{"ボヨヨン壁"}</td>
 					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{'/'}</code></pre>
+							<pre><code>{'\\'}</code></pre>
+						</th>
+						<td>{"パス (空きマス)"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{'@'}</code></pre>
+						</th>
+						<td>{"プレイヤー"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{'>'}</code></pre>
+						</th>
+						<td>{"階段"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{'m'}</code></pre>
+							<pre><code>{'M'}</code></pre>
+						</th>
+						<td>{"モンスター・倍速モンスター"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{"↑↓←→"}</code></pre>
+						</th>
+						<td>{"マップをクリックしてカーソルを移動し、上の文字キーでマスを書き換えます"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{"Insert"}</code></pre>
+							<pre><code>{"Delete"}</code></pre>
+						</th>
+						<td>{"カーソルの行を挿入・削除します (Shift を押しながらだと列)"}</td>
+					</tr>
+					<tr>
+						<th scope="row">
+							<pre><code>{"!shiren6-map v1"}</code></pre>
+						</th>
+						<td>{"1行目に書くと、続けて "}<code>{"name:"}</code>{" "}<code>{"floor:"}</code>{" "}<code>{"note:"}</code>{" の行と "}<code>{"//"}</code>{" のコメント行が書けます"}</td>
+					</tr>
 				</tbody>
 			</table>
 		</div>
 	}
 }
 
+#[derive(PartialEq, Properties)]
+struct ExportAreaProperties {
+	map: UseStateHandle<MapResult>,
+	path: UseStateHandle<Option<Vec<u8>>>,
+	selected: UseStateHandle<Option<usize>>,
+}
+
+#[function_component(ExportArea)]
+fn export_area(props: &ExportAreaProperties) -> Html {
+	let select_ref = use_node_ref();
+
+	// the representative is the starting cell, and its layer tells the diagonal it runs along
+	let start = props.selected.and_then(|v| {
+		let cells = &props.map.as_ref().ok()?.cells;
+		let (_, width) = cells.get_size();
+		let directions = if v < cells.len() {
+			[Direction::UpLeft, Direction::DownRight]
+		} else {
+			[Direction::UpRight, Direction::DownLeft]
+		};
+		let v = v % cells.len();
+		Some((v / width, v % width, directions))
+	});
+
+	let get_marker = {
+		let select_ref = select_ref.clone();
+
+		move || {
+			let select = select_ref.cast::<HtmlSelectElement>()?;
+			let (y, x, directions) = start?;
+			let direction = match select.value().as_str() {
+				"start" => None,
+				"direction0" => Some(directions[0]),
+				"direction1" => Some(directions[1]),
+				_ => return None,
+			};
+			Some(Marker { y, x, direction })
+		}
+	};
+
+	let onclick_svg = {
+		let map_handle = props.map.clone();
+		let path_handle = props.path.clone();
+		let get_marker = get_marker.clone();
+
+		Callback::from(move |_| {
+			if let Ok(map) = map_handle.as_ref() {
+				let svg = export::to_svg(&map.cells, path_handle.as_deref(), get_marker());
+				let _ = download("bouncy_walls.svg", "image/svg+xml", svg.as_bytes());
+			}
+		})
+	};
+
+	let onclick_png = {
+		let map_handle = props.map.clone();
+		let path_handle = props.path.clone();
+
+		Callback::from(move |_| {
+			if let Ok(map) = map_handle.as_ref() {
+				let png = export::to_png(&map.cells, path_handle.as_deref(), get_marker());
+				let _ = download("bouncy_walls.png", "image/png", &png);
+			}
+		})
+	};
+
+	let ascii = props
+		.map
+		.as_ref()
+		.ok()
+		.zip(props.path.as_deref())
+		.map(|(map, path)| export::to_ascii(&map.cells, path));
+
+	let onclick_text = {
+		let ascii = ascii.clone();
+
+		Callback::from(move |_| {
+			if let Some(ascii) = ascii.as_ref() {
+				let _ = download("bouncy_walls.txt", "text/plain", ascii.as_bytes());
+			}
+		})
+	};
+
+	let onclick_json = {
+		let map_handle = props.map.clone();
+
+		Callback::from(move |_| {
+			if let Ok(map) = map_handle.as_ref() {
+				let json = json::export(map);
+				let _ = download("bouncy_walls.json", "application/json", json.as_bytes());
+			}
+		})
+	};
+
+	let disabled = props.map.is_err();
+
+	html! {
+		<div class="export_area">
+			<select ref={select_ref} disabled={start.is_none()}>
+				<option value="none" selected={true}>{"マーカーなし"}</option>
+				if let Some((_, _, directions)) = start {
+					<option value="start">{"始点"}</option>
+					<option value="direction0">{"始点 "}{directions[0].get_arrow()}</option>
+					<option value="direction1">{"始点 "}{directions[1].get_arrow()}</option>
+				}
+			</select>
+			<button onclick={onclick_svg} disabled={disabled}>{"SVG保存"}</button>
+			<button onclick={onclick_png} disabled={disabled}>{"PNG保存"}</button>
+			<button onclick={onclick_text} disabled={ascii.is_none()}>{"テキスト保存"}</button>
+			<button onclick={onclick_json} disabled={disabled}>{"JSON保存"}</button>
+			if let Some(ascii) = ascii {
+				<textarea readonly=true rows={10} spellcheck="false" value={ascii}/>
+			}
+		</div>
+	}
+}
+
 const INITIAL_CELLS: &str = r#"#bbbbbbbb##
 bb......bbb
 b
@@ -193,14 +477,115 @@ bbbb....bbb
 
 #[function_component(BouncyWalls)]
 pub fn bouncy_walls() -> Html {
-	let cells: UseStateHandle<CellsResult> = use_state(|| TryFrom::try_from(INITIAL_CELLS));
-	let representatives = use_state(|| cells.clone().as_ref().unwrap().find_routes());
+	let map: UseStateHandle<MapResult> = use_state(|| TryFrom::try_from(INITIAL_CELLS));
+	let text = use_state(|| AttrValue::from(INITIAL_CELLS));
+	let representatives = use_state(|| map.as_ref().unwrap().cells.find_routes());
+	let (analyze, busy) = use_analysis(representatives.clone());
 	let path = use_state(|| None);
+	let selected = use_state(|| None);
+	let cursor = use_state(|| (0, 0));
+
+	let set_text = {
+		let map = map.clone();
+		let text = text.clone();
+		let path = path.clone();
+		let selected = selected.clone();
+
+		Callback::from(move |value: String| {
+			analyze.emit(value.clone());
+			map.set(Map::try_from(value.as_str()));
+			text.set(AttrValue::from(value));
+			path.set(None);
+			selected.set(None);
+		})
+	};
+
+	let onkeydown = {
+		let map = map.clone();
+		let cursor = cursor.clone();
+		let set_text = set_text.clone();
+
+		Callback::from(move |e: KeyboardEvent| {
+			let Ok(map) = map.as_ref() else {
+				return;
+			};
+			let (height, width) = map.cells.get_size();
+			if height == 0 {
+				return;
+			}
+			let (y, x) = (cursor.0.min(height - 1), cursor.1.min(width - 1));
+
+			let mut map = map.clone();
+			match e.key().as_str() {
+				"ArrowUp" => cursor.set((y.saturating_sub(1), x)),
+				"ArrowDown" => cursor.set(((y + 1).min(height - 1), x)),
+				"ArrowLeft" => cursor.set((y, x.saturating_sub(1))),
+				"ArrowRight" => cursor.set((y, (x + 1).min(width - 1))),
+				"Insert" if e.shift_key() => {
+					if map.insert_column(x).is_err() {
+						return;
+					}
+				}
+				"Insert" => {
+					if map.insert_row(y).is_err() {
+						return;
+					}
+				}
+				"Delete" if e.shift_key() => map.remove_column(x),
+				"Delete" => map.remove_row(y),
+				key => {
+					let mut chars = key.chars();
+					let (Some(c), None) = (chars.next(), chars.next()) else {
+						return;
+					};
+					if let Some(kind) = CellKind::from_char(c) {
+						map.set_cell(y, x, kind);
+					} else if let Some(kind) = EntityKind::from_char(c) {
+						map.set_entity(y, x, Some(kind));
+					} else {
+						return;
+					}
+				}
+			}
+			e.prevent_default();
+
+			if !e.key().starts_with("Arrow") {
+				let (height, width) = map.cells.get_size();
+				cursor.set((y.min(height - 1), x.min(width - 1)));
+				set_text.emit(map.to_string());
+			}
+		})
+	};
+
+	let overlay = use_state(|| Overlay::None);
+	// planning takes a while on a large floor, so it is redone only when the map or rules change
+	let plan = use_memo((map.clone(), overlay.get_escape_rules()), |(map, rules)| {
+		Some(plan_escape(map.as_ref().ok()?, (*rules)?))
+	});
+	let annotation = map
+		.as_ref()
+		.ok()
+		.map(|map| overlay.annotate(map, *cursor, plan.as_ref().as_ref()))
+		.unwrap_or_default();
+
+	let header = map.as_ref().ok().map(|map| &map.header);
 
 	html! {
 		<div class="bouncy_walls">
-			<View cells={cells.clone()} path={path.clone()}/>
-			<InputArea cells={cells} representatives={representatives.clone()} path={path}/>
+			if let Some(header) = header.filter(|header| header.name.is_some() || header.floor.is_some() || !header.notes.is_empty()) {
+				<div class="map_header">
+					if let Some(name) = header.name.clone() {
+						<span class="name">{name}</span>
+					}
+					if let Some(floor) = header.floor {
+						<span class="floor">{floor}{"F"}</span>
+					}
+					{header.notes.iter().map(|note| html! { <div class="note">{note}</div> }).collect::<Html>()}
+				</div>
+			}
+			<View map={map.clone()} path={path.clone()} cursor={cursor} onkeydown={onkeydown} labels={Rc::new(annotation.labels)} hide_pieces={annotation.moves_pieces}/>
+			<OverlayArea overlay={overlay} description={annotation.description} turns={annotation.turns}/>
+			<InputArea map={map.clone()} text={(*text).clone()} set_text={set_text} representatives={representatives.clone()} busy={busy} path={path} selected={selected}/>
 		</div>
 	}
 }
@@ -0,0 +1,291 @@
+use yew::prelude::*;
+
+use shiren6_core::bouncy_walls::{
+	escape::{EscapeError, Plan, MAX_DETOUR},
+	heading::Heading,
+	line_of_fire::line_of_fire,
+	map::{EntityKind, Map},
+	walk::WalkRules,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+
+use super::Label;
+
+/// What to annotate the tiles of `View` with.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+	None,
+	LineOfFire,
+	Walk(WalkRules),
+	/// the escape plan as of `turn`
+	Escape {
+		rules: WalkRules,
+		turn: usize,
+	},
+}
+
+#[derive(Default)]
+pub struct Annotation {
+	/// one per tile in row-major order, or empty
+	pub labels: Vec<Option<Label>>,
+	pub description: AttrValue,
+	/// the number of turns the simulation can be stepped through
+	pub turns: usize,
+	/// whether `labels` draw the player and monsters where they are, in place of the map's
+	pub moves_pieces: bool,
+}
+
+impl Overlay {
+	fn get_rules(&self) -> WalkRules {
+		match *self {
+			Overlay::Walk(rules) | Overlay::Escape { rules, .. } => rules,
+			_ => WalkRules::default(),
+		}
+	}
+
+	/// The rules to plan an escape by, when the plan is shown.
+	pub fn get_escape_rules(&self) -> Option<WalkRules> {
+		match *self {
+			Overlay::Escape { rules, .. } => Some(rules),
+			_ => None,
+		}
+	}
+
+	fn with_rules(self, rules: WalkRules) -> Self {
+		match self {
+			Overlay::Walk(_) => Overlay::Walk(rules),
+			Overlay::Escape { turn, .. } => Overlay::Escape { rules, turn },
+			overlay => overlay,
+		}
+	}
+
+	/// `plan` is `plan_escape` for `get_escape_rules`, which is left to the caller to keep
+	/// across renders.
+	pub fn annotate(
+		&self,
+		map: &Map,
+		cursor: (usize, usize),
+		plan: Option<&Result<Plan, EscapeError>>,
+	) -> Annotation {
+		let (height, width) = map.cells.get_size();
+		let player = map
+			.entities
+			.iter()
+			.find(|entity| entity.kind == EntityKind::Player)
+			.map(|entity| (entity.y, entity.x));
+		let cursor = Some(cursor).filter(|&(y, x)| y < height && x < width);
+
+		let label = |text: String, title: String, class: &'static str| {
+			Some(Label {
+				text: AttrValue::from(text),
+				title: AttrValue::from(title),
+				class,
+			})
+		};
+		let describe = |description: &'static str| Annotation {
+			description: AttrValue::from(description),
+			..Default::default()
+		};
+		// numbers the steps of a walk with the direction they are taken in
+		let label_steps = |labels: &mut Vec<Option<Label>>, path: &[(usize, usize)]| {
+			for (turn, pair) in path.windows(2).enumerate() {
+				let [(y, x), (ny, nx)] = [pair[0], pair[1]];
+				let Some(heading) =
+					Heading::from_delta(ny as isize - y as isize, nx as isize - x as isize)
+				else {
+					// waiting
+					continue;
+				};
+				labels[ny * width + nx] = label(
+					format!("{}{}", heading.get_arrow(), turn + 1),
+					format!("{}ターン目", turn + 1),
+					"",
+				);
+			}
+		};
+
+		match *self {
+			Overlay::None => Annotation::default(),
+			Overlay::LineOfFire => {
+				let Some((y, x)) = player.or(cursor) else {
+					return Annotation::default();
+				};
+				let labels = line_of_fire(map, y, x)
+					.into_iter()
+					.map(|hit| {
+						hit.and_then(|hit| {
+							label(
+								format!("{}{}", hit.heading.get_arrow(), hit.bounces),
+								format!("{}マス先", hit.distance),
+								"",
+							)
+						})
+					})
+					.collect();
+				Annotation {
+					labels,
+					description: AttrValue::from(
+						"@ の位置 (なければカーソルの位置) から撃ったときの向きと反射回数",
+					),
+					turns: 0,
+					moves_pieces: false,
+				}
+			}
+			Overlay::Walk(rules) => {
+				let (Some(from), Some(to)) = (player, cursor) else {
+					return describe("@ を置いてください");
+				};
+				let Some(path) = rules.find_path(&map.cells, from, to) else {
+					return describe("カーソルの位置には行けません");
+				};
+
+				let mut labels = vec![None; map.cells.len()];
+				label_steps(&mut labels, &path);
+				Annotation {
+					labels,
+					description: AttrValue::from(format!(
+						"@ からカーソルの位置まで {}ターン",
+						path.len() - 1
+					)),
+					turns: 0,
+					moves_pieces: false,
+				}
+			}
+			Overlay::Escape { turn, .. } => {
+				let plan = match plan {
+					None => return Annotation::default(),
+					Some(Ok(plan)) => plan,
+					Some(Err(EscapeError::NoPlayer)) => return describe("@ を置いてください"),
+					Some(Err(EscapeError::NoStairs)) => return describe("> を置いてください"),
+					Some(Err(EscapeError::Unreachable)) => return describe("階段に行けません"),
+					Some(Err(EscapeError::DetourTooLong)) => {
+						return Annotation {
+							description: AttrValue::from(format!(
+								"{MAX_DETOUR}ターンを超える回り道が必要なため計算できません"
+							)),
+							..Default::default()
+						}
+					}
+					Some(Err(EscapeError::TooComplex)) => {
+						return describe("モンスターが多すぎて計算できません")
+					}
+				};
+
+				let path: Vec<_> = plan.turns.iter().map(|turn| turn.player).collect();
+				let mut labels = vec![None; map.cells.len()];
+				label_steps(&mut labels, &path);
+
+				let turn = turn.min(plan.turns.len() - 1);
+				let current = &plan.turns[turn];
+				let (y, x) = current.player;
+				labels[y * width + x] =
+					label(String::from("@"), String::from("プレイヤー"), "player");
+				for monster in current.monsters.iter() {
+					labels[monster.y * width + monster.x] = if monster.fast {
+						label(String::from("M"), String::from("倍速モンスター"), "monster")
+					} else {
+						label(String::from("m"), String::from("モンスター"), "monster")
+					};
+				}
+
+				let description = format!(
+					"{}ターンで階段へ (モンスターの隣: {}ターン) / {}ターン目{}",
+					plan.turns.len() - 1,
+					plan.adjacent_turns,
+					turn,
+					if current.adjacent { ": 隣接" } else { "" },
+				);
+				Annotation {
+					labels,
+					description: AttrValue::from(description),
+					turns: plan.turns.len(),
+					moves_pieces: true,
+				}
+			}
+		}
+	}
+}
+
+#[derive(PartialEq, Properties)]
+pub struct OverlayAreaProperties {
+	pub overlay: UseStateHandle<Overlay>,
+	pub description: AttrValue,
+	pub turns: usize,
+}
+
+#[function_component(OverlayArea)]
+pub fn overlay_area(props: &OverlayAreaProperties) -> Html {
+	let rules = props.overlay.get_rules();
+
+	let onchange = {
+		let overlay = props.overlay.clone();
+
+		Callback::from(move |e: Event| {
+			if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+				overlay.set(match select.value().as_str() {
+					"line_of_fire" => Overlay::LineOfFire,
+					"walk" => Overlay::Walk(rules),
+					"escape" => Overlay::Escape { rules, turn: 0 },
+					_ => Overlay::None,
+				});
+			}
+		})
+	};
+
+	let onchange_rule = |set: fn(&mut WalkRules, bool)| {
+		let overlay = props.overlay.clone();
+
+		Callback::from(move |e: Event| {
+			if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+				let mut rules = rules;
+				set(&mut rules, input.checked());
+				overlay.set(overlay.with_rules(rules));
+			}
+		})
+	};
+
+	let set_turn = |turn: usize| {
+		let overlay = props.overlay.clone();
+
+		move |_| overlay.set(Overlay::Escape { rules, turn })
+	};
+	let oninput_turn = {
+		let overlay = props.overlay.clone();
+
+		Callback::from(move |e: InputEvent| {
+			if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+				let turn = input.value().parse().unwrap_or(0);
+				overlay.set(Overlay::Escape { rules, turn });
+			}
+		})
+	};
+
+	html! {
+		<div class="overlay">
+			<select onchange={onchange}>
+				<option value="none" selected={*props.overlay == Overlay::None}>{"表示なし"}</option>
+				<option value="line_of_fire" selected={*props.overlay == Overlay::LineOfFire}>{"射線 (矢・魔法弾)"}</option>
+				<option value="walk" selected={matches!(*props.overlay, Overlay::Walk(_))}>{"移動経路"}</option>
+				<option value="escape" selected={matches!(*props.overlay, Overlay::Escape { .. })}>{"階段への逃走"}</option>
+			</select>
+			if matches!(*props.overlay, Overlay::Walk(_) | Overlay::Escape { .. }) {
+				<label>
+					<input type="checkbox" checked={rules.through_water} onchange={onchange_rule(|rules, checked| rules.through_water = checked)}/>
+					{"水を通れる"}
+				</label>
+				<label>
+					<input type="checkbox" checked={rules.through_pits} onchange={onchange_rule(|rules, checked| rules.through_pits = checked)}/>
+					{"穴を通れる"}
+				</label>
+			}
+			if let (Overlay::Escape { turn, .. }, true) = (*props.overlay, props.turns > 0) {
+				<span class="turns">
+					<button onclick={set_turn(turn.saturating_sub(1))} disabled={turn == 0}>{"◀"}</button>
+					<input type="range" min="0" max={(props.turns - 1).to_string()} value={turn.min(props.turns - 1).to_string()} oninput={oninput_turn}/>
+					<button onclick={set_turn(turn + 1)} disabled={turn + 1 >= props.turns}>{"▶"}</button>
+				</span>
+			}
+			<span>{props.description.clone()}</span>
+		</div>
+	}
+}
@@ -0,0 +1,299 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{DedicatedWorkerGlobalScope, Event, MessageEvent};
+use yew::prelude::*;
+
+use shiren6_core::bouncy_walls::{
+	cell::Cells,
+	components::Components,
+	map::{Map, MapResult},
+};
+
+// the loader bundled from worker.js, relative to the page
+const WORKER_PATH: &str = "./pkg/worker.js";
+// how long a search may run before a newer map restarts the worker instead of waiting for it
+const CANCEL_AFTER_MS: f64 = 1000.0;
+
+#[derive(Serialize, Deserialize)]
+struct AnalysisRequest {
+	id: u32,
+	text: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AnalysisResponse {
+	id: u32,
+	representatives: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum FromWorker {
+	// sent once, as messages posted before the worker listens are lost
+	Loaded,
+	Analyzed(AnalysisResponse),
+}
+
+/// Runs `find_routes` off the UI thread. The previous map is kept so that an edit of a few
+/// tiles only recomputes the components around them.
+pub struct AnalysisWorker {
+	previous: Option<(Cells, Components)>,
+}
+
+impl AnalysisWorker {
+	/// Answers the requests posted to this worker, after telling the page it is ready.
+	pub fn run() {
+		let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+		let mut worker = Self { previous: None };
+
+		let onmessage = {
+			let scope = scope.clone();
+
+			Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+				let Some(request) = e
+					.data()
+					.as_string()
+					.and_then(|data| serde_json::from_str::<AnalysisRequest>(&data).ok())
+				else {
+					return;
+				};
+				let map: MapResult = Map::try_from(request.text.as_str());
+				let representatives = match map {
+					Ok(map) => worker.analyze(map.cells),
+					Err(_) => Vec::new(),
+				};
+				post(
+					&scope,
+					&FromWorker::Analyzed(AnalysisResponse {
+						id: request.id,
+						representatives,
+					}),
+				);
+			})
+		};
+		scope.set_onmessage(Some(&onmessage.into_js_value().unchecked_into()));
+		post(&scope, &FromWorker::Loaded);
+	}
+
+	fn analyze(&mut self, cells: Cells) -> Vec<usize> {
+		let components = match self.previous.take() {
+			Some((previous, mut components)) => {
+				match previous.get_changes(&cells) {
+					Some(changes) => components.update(&cells, &changes),
+					None => components = Components::new(&cells),
+				}
+				components
+			}
+			None => Components::new(&cells),
+		};
+		let representatives = components.find_routes();
+		self.previous = Some((cells, components));
+
+		representatives
+	}
+}
+
+fn post(scope: &DedicatedWorkerGlobalScope, message: &FromWorker) {
+	if let Ok(message) = serde_json::to_string(message) {
+		let _ = scope.post_message(&JsValue::from_str(&message));
+	}
+}
+
+// Only one request is in flight at a time. Requests made meanwhile replace each other in
+// `queued`, and a response is dropped when a newer request exists, so intermediate
+// keystrokes are never searched.
+struct Analysis {
+	connection: Option<Connection>,
+	// set once the worker cannot be loaded, after which maps are searched on this thread
+	fallback: bool,
+	latest: u32,
+	latest_text: String,
+	// when the request being searched was sent
+	in_flight: Option<f64>,
+	queued: Option<AnalysisRequest>,
+	representatives: UseStateHandle<Vec<usize>>,
+	busy: UseStateHandle<bool>,
+}
+
+struct Connection {
+	worker: web_sys::Worker,
+	loaded: bool,
+}
+
+impl Drop for Connection {
+	fn drop(&mut self) {
+		self.worker.set_onerror(None);
+		self.worker.set_onmessage(None);
+		self.worker.terminate();
+	}
+}
+
+impl Analysis {
+	fn request(this: &Rc<RefCell<Self>>, text: String) {
+		let mut analysis = this.borrow_mut();
+		analysis.latest += 1;
+		analysis.latest_text.clone_from(&text);
+		if analysis.fallback {
+			analysis.analyze_here();
+			return;
+		}
+		analysis.busy.set(true);
+		analysis.queued = Some(AnalysisRequest {
+			id: analysis.latest,
+			text,
+		});
+
+		if analysis
+			.in_flight
+			.is_some_and(|sent| js_sys::Date::now() - sent > CANCEL_AFTER_MS)
+		{
+			analysis.connection = None;
+			analysis.in_flight = None;
+		}
+		if analysis.connection.is_none() {
+			analysis.connection = Self::spawn(this);
+			if analysis.connection.is_none() {
+				analysis.fallback = true;
+				analysis.queued = None;
+				analysis.analyze_here();
+				return;
+			}
+		}
+		analysis.send_queued();
+	}
+
+	// `None` when workers cannot be created at all
+	fn spawn(this: &Rc<RefCell<Self>>) -> Option<Connection> {
+		let worker = web_sys::Worker::new(WORKER_PATH).ok()?;
+
+		// owned by the worker object rather than `Connection`, which an error handler drops
+		let onerror = {
+			let weak = Rc::downgrade(this);
+
+			Closure::<dyn FnMut(Event)>::new(move |_| {
+				if let Some(this) = weak.upgrade() {
+					Self::fail(&this);
+				}
+			})
+		};
+		let onmessage = {
+			let weak = Rc::downgrade(this);
+
+			Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+				let Some(message) = e
+					.data()
+					.as_string()
+					.and_then(|data| serde_json::from_str::<FromWorker>(&data).ok())
+				else {
+					return;
+				};
+				if let Some(this) = weak.upgrade() {
+					Self::receive(&this, message);
+				}
+			})
+		};
+		worker.set_onerror(Some(&onerror.into_js_value().unchecked_into()));
+		worker.set_onmessage(Some(&onmessage.into_js_value().unchecked_into()));
+
+		Some(Connection {
+			worker,
+			loaded: false,
+		})
+	}
+
+	// sends the queued request once the worker has loaded and is not searching
+	fn send_queued(&mut self) {
+		let Some(connection) = self.connection.as_ref().filter(|c| c.loaded) else {
+			return;
+		};
+		if self.in_flight.is_some() {
+			return;
+		}
+		let Some(request) = self.queued.take() else {
+			return;
+		};
+		if let Ok(message) = serde_json::to_string(&request) {
+			let _ = connection.worker.post_message(&JsValue::from_str(&message));
+			self.in_flight = Some(js_sys::Date::now());
+		}
+	}
+
+	// A worker that never loaded will not load the next time either, so maps are searched here
+	// from then on. One that crashed while searching is replaced for the maps after that one.
+	fn fail(this: &Rc<RefCell<Self>>) {
+		let mut analysis = this.borrow_mut();
+		let loaded = analysis.connection.take().is_some_and(|c| c.loaded);
+		analysis.in_flight = None;
+
+		if !loaded {
+			analysis.fallback = true;
+			analysis.queued = None;
+			analysis.analyze_here();
+			return;
+		}
+		if analysis.queued.is_some() {
+			analysis.connection = Self::spawn(this);
+			if analysis.connection.is_some() {
+				return;
+			}
+			analysis.queued = None;
+		}
+		analysis.representatives.set(Vec::new());
+		analysis.busy.set(false);
+	}
+
+	fn analyze_here(&self) {
+		let representatives = Map::try_from(self.latest_text.as_str())
+			.map(|map| map.cells.find_routes())
+			.unwrap_or_default();
+		self.representatives.set(representatives);
+		self.busy.set(false);
+	}
+
+	fn receive(this: &Rc<RefCell<Self>>, message: FromWorker) {
+		let mut analysis = this.borrow_mut();
+
+		match message {
+			FromWorker::Loaded => {
+				if let Some(connection) = analysis.connection.as_mut() {
+					connection.loaded = true;
+				}
+			}
+			FromWorker::Analyzed(response) => {
+				analysis.in_flight = None;
+				if response.id == analysis.latest {
+					analysis.representatives.set(response.representatives);
+					analysis.busy.set(false);
+				}
+			}
+		}
+		analysis.send_queued();
+	}
+}
+
+/// Returns a callback that searches the given map text in the worker and stores the result
+/// into `representatives`, and whether a search is still running.
+#[hook]
+pub fn use_analysis(representatives: UseStateHandle<Vec<usize>>) -> (Callback<String>, bool) {
+	let busy = use_state(|| false);
+
+	let analysis = {
+		let busy = busy.clone();
+
+		use_mut_ref(move || Analysis {
+			connection: None,
+			fallback: false,
+			latest: 0,
+			latest_text: String::new(),
+			in_flight: None,
+			queued: None,
+			representatives,
+			busy,
+		})
+	};
+
+	let request = Callback::from(move |text| Analysis::request(&analysis, text));
+
+	(request, *busy)
+}
@@ -0,0 +1,34 @@
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+// long enough for the browser to start reading the blob
+const REVOKE_DELAY_MS: i32 = 60_000;
+
+/// Lets the browser save `data` as a file through a temporary `<a download>`.
+pub fn download(file_name: &str, mime_type: &str, data: &[u8]) -> Result<(), JsValue> {
+	let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+	let options = BlobPropertyBag::new();
+	options.set_type(mime_type);
+	let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+	let url = Url::create_object_url_with_blob(&blob)?;
+
+	let window =
+		web_sys::window().ok_or_else(|| JsValue::from_str("Could not obtain the window."))?;
+	let document = window
+		.document()
+		.ok_or_else(|| JsValue::from_str("Could not obtain the document."))?;
+	let anchor: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+	anchor.set_href(&url);
+	anchor.set_download(file_name);
+	anchor.click();
+
+	// revoking right after the click cancels the download in some browsers
+	let revoke = Closure::once_into_js(move || {
+		let _ = Url::revoke_object_url(&url);
+	});
+	window.set_timeout_with_callback_and_timeout_and_arguments_0(
+		revoke.unchecked_ref(),
+		REVOKE_DELAY_MS,
+	)?;
+	Ok(())
+}
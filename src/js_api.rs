@@ -0,0 +1,104 @@
+use shiren6_core::{
+	bouncy_walls::map::Map,
+	price_list::{
+		items::{self, Item, ItemState},
+		Price,
+	},
+};
+use wasm_bindgen::prelude::*;
+
+/// A map parsed from the textarea format, for use from JavaScript.
+#[wasm_bindgen(js_name = Cells)]
+pub struct JsCells {
+	map: Map,
+}
+
+#[wasm_bindgen(js_class = Cells)]
+impl JsCells {
+	/// Parses a map in the same format as the textarea, versioned or bare.
+	#[wasm_bindgen(constructor)]
+	pub fn new(text: &str) -> Result<JsCells, JsError> {
+		let map = Map::try_from(text).map_err(|e| JsError::new(&format!("{e:?}")))?;
+		Ok(Self { map })
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn height(&self) -> u32 {
+		self.map.cells.get_size().0 as u32
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn width(&self) -> u32 {
+		self.map.cells.get_size().1 as u32
+	}
+
+	/// Representatives of the success paths, `layer * height * width + y * width + x`.
+	#[wasm_bindgen(js_name = findRoutes)]
+	pub fn find_routes(&self) -> Vec<u32> {
+		self.map
+			.cells
+			.find_routes()
+			.into_iter()
+			.map(|v| v as u32)
+			.collect()
+	}
+
+	/// One bitmask per cell in row-major order: 1 along `\`, 2 along `/`.
+	pub fn trace(&self, representative: u32) -> Result<Vec<u8>, JsError> {
+		let v = representative as usize;
+		if v >= self.map.cells.len() * 2 {
+			return Err(JsError::new("representative out of range"));
+		}
+		Ok(self.map.cells.trace(v))
+	}
+
+	/// The map in the versioned text format.
+	#[wasm_bindgen(js_name = toString)]
+	pub fn to_text(&self) -> String {
+		self.map.to_string()
+	}
+}
+
+#[wasm_bindgen(getter_with_clone)]
+pub struct PriceMatch {
+	pub category: String,
+	pub name: String,
+	/// `"normal"`, `"blessed"` or `"cursed"`
+	pub state: String,
+	pub buy: Price,
+	pub sell: Price,
+	/// charges of a staff, capacity of a pot or size of a stack
+	pub count: Option<u16>,
+	/// `+N` of a weapon or shield
+	pub modifier: Option<i16>,
+	/// seal slots of a weapon or shield
+	pub slots: Option<u16>,
+}
+
+impl PriceMatch {
+	fn new(item: &Item) -> Self {
+		let state = match item.state {
+			ItemState::Normal => "normal",
+			ItemState::Blessed => "blessed",
+			ItemState::Cursed => "cursed",
+		};
+		Self {
+			category: String::from(item.category.id),
+			name: String::from(item.name),
+			state: String::from(state),
+			buy: item.buy,
+			sell: item.sell,
+			count: item.count,
+			modifier: item.modifier,
+			slots: item.slots,
+		}
+	}
+}
+
+/// Every item whose buy or sell price equals `price`.
+#[wasm_bindgen(js_name = lookupPrice)]
+pub fn lookup_price(price: Price) -> Vec<PriceMatch> {
+	items::filtered_items(Ok(Some(price)))
+		.map(PriceMatch::new)
+		.collect()
+}
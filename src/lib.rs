@@ -1,4 +1,6 @@
 mod bouncy_walls;
+mod download;
+mod js_api;
 mod price_list;
 
 use wasm_bindgen::prelude::*;
@@ -11,6 +13,8 @@ enum Route {
 	Home,
 	#[at("/bouncy-walls")]
 	BouncyWalls,
+	#[at("/bouncy-walls/diff")]
+	BouncyWallsDiff,
 	#[at("/price-list")]
 	PriceList,
 }
@@ -19,6 +23,7 @@ fn switch(routes: Route) -> Html {
 	match routes {
 		Route::Home => html! {},
 		Route::BouncyWalls => html! { <bouncy_walls::BouncyWalls /> },
+		Route::BouncyWallsDiff => html! { <bouncy_walls::BouncyWallsDiff /> },
 		Route::PriceList => html! { <price_list::PriceList /> },
 	}
 }
@@ -29,6 +34,7 @@ fn menu() -> Html {
 		<div>
 			<div><Link<Route> to={Route::Home}>{"Top"}</Link<Route>></div>
 			<div><Link<Route> to={Route::BouncyWalls}>{"ボヨヨン壁 成功パスサーチ"}</Link<Route>></div>
+			<div><Link<Route> to={Route::BouncyWallsDiff}>{"ボヨヨン壁 マップ比較"}</Link<Route>></div>
 			<div><Link<Route> to={Route::PriceList}>{"鑑定補助 値段検索"}</Link<Route>></div>
 		</div>
 	}
@@ -53,3 +59,8 @@ fn app() -> Html {
 pub fn run_app() {
 	yew::Renderer::<App>::new().render();
 }
+
+#[wasm_bindgen]
+pub fn run_worker() {
+	bouncy_walls::AnalysisWorker::run();
+}
@@ -0,0 +1,186 @@
+use shiren6_core::price_list::{
+	inference::{self, Observation},
+	items, Price,
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+pub(super) fn get_observation_str(observation: &Observation) -> String {
+	match observation {
+		Observation::Buy(price) => format!("買値 {price}"),
+		Observation::Sell(price) => format!("売値 {price}"),
+		Observation::Zaps(zaps) => format!("{zaps}回振れた"),
+		Observation::Holds(held) => format!("{held}個入った"),
+	}
+}
+
+fn parse_observation(kind: &str, value: &str) -> Option<Observation> {
+	match kind {
+		"buy" => value.parse::<Price>().ok().map(Observation::Buy),
+		"sell" => value.parse::<Price>().ok().map(Observation::Sell),
+		"zaps" => value.parse().ok().map(Observation::Zaps),
+		"holds" => value.parse().ok().map(Observation::Holds),
+		_ => None,
+	}
+}
+
+#[derive(PartialEq, Properties)]
+pub(super) struct ObservationFormProperties {
+	pub onadd: Callback<Observation>,
+}
+
+#[function_component(ObservationForm)]
+pub(super) fn observation_form(props: &ObservationFormProperties) -> Html {
+	let invalid = use_state(|| false);
+	let kind_ref = use_node_ref();
+	let value_ref = use_node_ref();
+
+	let onsubmit = {
+		let onadd = props.onadd.clone();
+		let invalid = invalid.clone();
+		let kind_ref = kind_ref.clone();
+		let value_ref = value_ref.clone();
+
+		Callback::from(move |e: SubmitEvent| {
+			e.prevent_default();
+			let (Some(kind), Some(input)) = (
+				kind_ref.cast::<HtmlSelectElement>(),
+				value_ref.cast::<HtmlInputElement>(),
+			) else {
+				return;
+			};
+			match parse_observation(&kind.value(), input.value().trim()) {
+				Some(observation) => {
+					onadd.emit(observation);
+					invalid.set(false);
+					input.set_value("");
+				}
+				None => invalid.set(true),
+			}
+		})
+	};
+
+	html! {
+		<form onsubmit={onsubmit}>
+			<select ref={kind_ref}>
+				<option value="buy">{"買値"}</option>
+				<option value="sell">{"売値"}</option>
+				<option value="zaps">{"振れた回数 (杖)"}</option>
+				<option value="holds">{"入った個数 (壺)"}</option>
+			</select>
+			<input ref={value_ref} inputmode="numeric" maxlength="7"/>
+			<button type="submit">{"追加"}</button>
+			if *invalid {
+				<span class="error">{"数字を入力してください"}</span>
+			}
+		</form>
+	}
+}
+
+#[function_component(Inference)]
+pub fn inference() -> Html {
+	// an index into `items::CATEGORIES`
+	let category = use_state(|| None::<usize>);
+	let observations = use_state(Vec::<Observation>::new);
+
+	let onchange_category = {
+		let category = category.clone();
+
+		Callback::from(move |e: Event| {
+			if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+				category.set(select.value().parse().ok());
+			}
+		})
+	};
+
+	let onadd = {
+		let observations = observations.clone();
+
+		Callback::from(move |observation| {
+			let mut list = (*observations).clone();
+			if !list.contains(&observation) {
+				list.push(observation);
+			}
+			observations.set(list);
+		})
+	};
+
+	let onremove = |i: usize| {
+		let observations = observations.clone();
+
+		Callback::from(move |_| {
+			let mut list = (*observations).clone();
+			list.remove(i);
+			observations.set(list);
+		})
+	};
+
+	let candidates = inference::infer(category.map(|i| &items::CATEGORIES[i]), &observations);
+
+	html! {
+		<div class="inference">
+			<div class="category">{"手がかりから絞り込み"}</div>
+			<div>
+				<select onchange={onchange_category}>
+					<option value="" selected={category.is_none()}>{"種類不明"}</option>
+					{items::CATEGORIES.iter().enumerate().map(|(i, c)| html! {
+						<option value={i.to_string()} selected={*category == Some(i)}>{c.name}</option>
+					}).collect::<Html>()}
+				</select>
+			</div>
+			<ObservationForm onadd={onadd}/>
+			<ul class="observations">
+			{
+				observations.iter().enumerate().map(|(i, observation)| html! {
+					<li>
+						{get_observation_str(observation)}
+						<button onclick={onremove(i)}>{"×"}</button>
+					</li>
+				}).collect::<Html>()
+			}
+			</ul>
+			if observations.is_empty() {
+				<div>{"手がかりを追加してください"}</div>
+			} else if candidates.is_empty() {
+				<div>{"当てはまるアイテムがありません"}</div>
+			} else {
+				<div>{format!("候補 {}件", candidates.len())}</div>
+				<table>
+					<thead>
+						<tr>
+							<th scope="col">{"種類"}</th>
+							<th scope="col" colspan="2">{"名前"}</th>
+							<th scope="col">{"詳細"}</th>
+							<th scope="col">{"買値"}</th>
+							<th scope="col">{"売値"}</th>
+						</tr>
+					</thead>
+					<tbody>
+					{
+						candidates.into_iter().map(|item| html! {
+							<tr>
+								<td>{item.category.name}</td>
+								<th class="state">{item.state.get_state_str()}</th>
+								<td class="name">{item.name}</td>
+								<td>
+								{
+									item.category
+										.get_columns()
+										.into_iter()
+										.zip(item.get_columns())
+										.map(|(column, value)| format!("{column}{value}"))
+										.collect::<Vec<_>>()
+										.join(" ")
+								}
+								</td>
+								<td>{item.buy}</td>
+								<td>{item.sell}</td>
+							</tr>
+						}).collect::<Html>()
+					}
+					</tbody>
+				</table>
+			}
+		</div>
+	}
+}
@@ -1,13 +1,15 @@
-mod items;
+mod inference;
+mod notebook;
 
-use std::num::ParseIntError;
+use std::num::{IntErrorKind, ParseIntError};
 
+use shiren6_core::price_list::{items, Price};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
 #[derive(Clone, PartialEq, Properties)]
 struct InputProps {
-	price: UseStateHandle<Result<Option<u16>, ParseIntError>>,
+	price: UseStateHandle<Result<Option<Price>, ParseIntError>>,
 }
 
 #[function_component(Input)]
@@ -26,177 +28,83 @@ fn input(props: &InputProps) -> Html {
 				let price = if value.is_empty() {
 					Ok(None)
 				} else {
-					value.parse::<u16>().map(|price| Some(price))
+					value.parse::<Price>().map(Some)
 				};
 				price_handle.set(price);
 			}
 		})
 	};
 
+	let error = props.price.as_ref().err().map(|e| match e.kind() {
+		IntErrorKind::PosOverflow => "値段が大きすぎます",
+		_ => "値段は数字で入力してください",
+	});
+
 	html! {
 		<div>
 			<label class="input_label">{"値段:"}</label>
-			<input oninput={oninput} ref={input_ref} inputmode="numeric" maxlength="7"/>
+			<input class={classes!(error.is_some().then_some("error"))} oninput={oninput} ref={input_ref} inputmode="numeric" maxlength="7"/>
+			if let Some(error) = error {
+				<span class="error">{error}</span>
+			}
 		</div>
 	}
 }
 
 #[derive(Clone, PartialEq, Properties)]
 struct ListProps {
-	price: UseStateHandle<Result<Option<u16>, ParseIntError>>,
+	price: UseStateHandle<Result<Option<Price>, ParseIntError>>,
 }
 
 #[function_component(List)]
 fn list(props: &ListProps) -> Html {
 	let price = (*props.price).clone();
 
-	let mut bracelets: Vec<_> = items::filtered_bracelets(price.clone()).collect();
-	if let Ok(&Some(price)) = price.as_ref() {
-		bracelets.sort_by(|a, b| (b.buy == price).cmp(&(a.buy == price)));
-	}
-	let mut grasses: Vec<_> = items::filtered_grasses(price.clone()).collect();
-	if let Ok(&Some(price)) = price.as_ref() {
-		grasses.sort_by(|a, b| (b.buy == price).cmp(&(a.buy == price)));
-	}
-	let mut scrolls: Vec<_> = items::filtered_scrolls(price.clone()).collect();
-	if let Ok(&Some(price)) = price.as_ref() {
-		scrolls.sort_by(|a, b| (b.buy == price).cmp(&(a.buy == price)));
-	}
-	let mut staves: Vec<_> = items::filtered_staves(price.clone()).collect();
-	if let Ok(&Some(price)) = price.as_ref() {
-		staves.sort_by(|a, b| (b.buy == price).cmp(&(a.buy == price)));
-	}
-	let mut pots: Vec<_> = items::filtered_pots(price.clone()).collect();
-	if let Ok(&Some(price)) = price.as_ref() {
-		pots.sort_by(|a, b| (b.buy == price).cmp(&(a.buy == price)));
-	}
-
 	html! {
 		<div class="container">
-			<div class="category">{"腕輪"}</div>
-			<table>
-				if bracelets.len() > 0 {
-					<thead>
-						<tr>
-							<th scope="col" colspan="2">{"名前"}</th>
-							<th scope="col">{"買値"}</th>
-							<th scope="col">{"売値"}</th>
-						</tr>
-					</thead>
-					<tbody>
-					{
-						bracelets.into_iter().map(|item| html! {
-							<tr>
-								<th class="state">{item.state.get_state_str()}</th>
-								<td class="name">{item.name}</td>
-								<td>{item.buy}</td>
-								<td>{item.sell}</td>
-							</tr>
-						}).collect::<Html>()
-					}
-					</tbody>
-				}
-			</table>
-			<div class="category">{"草"}</div>
-			<table>
-				if grasses.len() > 0 {
-					<thead>
-						<tr>
-							<th scope="col" colspan="2">{"名前"}</th>
-							<th scope="col">{"買値"}</th>
-							<th scope="col">{"売値"}</th>
-						</tr>
-					</thead>
-					<tbody>
-					{
-						grasses.into_iter().map(|item| html! {
-							<tr>
-								<th class="state">{item.state.get_state_str()}</th>
-								<td class="name">{item.name}</td>
-								<td>{item.buy}</td>
-								<td>{item.sell}</td>
-							</tr>
-						}).collect::<Html>()
-					}
-					</tbody>
+		{
+			items::CATEGORIES.iter().map(|category| {
+				let mut items: Vec<_> = items::filtered_category(category, price.clone()).collect();
+				if let Ok(&Some(price)) = price.as_ref() {
+					items.sort_by_key(|item| item.buy != price);
 				}
-			</table>
-			<div class="category">{"巻物"}</div>
-			<table>
-				if scrolls.len() > 0 {
-					<thead>
-						<tr>
-							<th scope="col" colspan="2">{"名前"}</th>
-							<th scope="col">{"買値"}</th>
-							<th scope="col">{"売値"}</th>
-						</tr>
-					</thead>
-					<tbody>
-					{
-						scrolls.into_iter().map(|item| html! {
-							<tr>
-								<th class="state">{item.state.get_state_str()}</th>
-								<td class="name">{item.name}</td>
-								<td>{item.buy}</td>
-								<td>{item.sell}</td>
-							</tr>
-						}).collect::<Html>()
-					}
-					</tbody>
-				}
-			</table>
-			<div class="category">{"杖"}</div>
-			<table>
-				if staves.len() > 0 {
-					<thead>
-						<tr>
-							<th scope="col" colspan="2">{"名前"}</th>
-							<th scope="col">{"回数"}</th>
-							<th scope="col">{"買値"}</th>
-							<th scope="col">{"売値"}</th>
-						</tr>
-					</thead>
-					<tbody>
-					{
-						staves.into_iter().map(|item| html! {
-							<tr>
-								<th class="state">{item.state.get_state_str()}</th>
-								<td class="name">{item.name}</td>
-								<td>{item.count}</td>
-								<td>{item.buy}</td>
-								<td>{item.sell}</td>
-							</tr>
-						}).collect::<Html>()
-					}
-					</tbody>
-				}
-			</table>
-			<div class="category">{"壺"}</div>
-			<table>
-				if pots.len() > 0 {
-					<thead>
-						<tr>
-							<th scope="col" colspan="2">{"名前"}</th>
-							<th scope="col">{"容量"}</th>
-							<th scope="col">{"買値"}</th>
-							<th scope="col">{"売値"}</th>
-						</tr>
-					</thead>
-					<tbody>
-					{
-						pots.into_iter().map(|item| html! {
-							<tr>
-								<th class="state">{item.state.get_state_str()}</th>
-								<td class="name">{item.name}</td>
-								<td>{item.size}</td>
-								<td>{item.buy}</td>
-								<td>{item.sell}</td>
-							</tr>
-						}).collect::<Html>()
-					}
-					</tbody>
+
+				html! {
+					<>
+						<div class="category">{category.name}</div>
+						<table>
+							if !items.is_empty() {
+								<thead>
+									<tr>
+										<th scope="col" colspan="2">{"名前"}</th>
+										{category.get_columns().into_iter().map(|column| html! {
+											<th scope="col">{column}</th>
+										}).collect::<Html>()}
+										<th scope="col">{"買値"}</th>
+										<th scope="col">{"売値"}</th>
+									</tr>
+								</thead>
+								<tbody>
+								{
+									items.into_iter().map(|item| html! {
+										<tr>
+											<th class="state">{item.state.get_state_str()}</th>
+											<td class="name">{item.name}</td>
+											{item.get_columns().into_iter().map(|column| html! {
+												<td>{column}</td>
+											}).collect::<Html>()}
+											<td>{item.buy}</td>
+											<td>{item.sell}</td>
+										</tr>
+									}).collect::<Html>()
+								}
+								</tbody>
+							}
+						</table>
+					</>
 				}
-			</table>
+			}).collect::<Html>()
+		}
 		</div>
 	}
 }
@@ -208,6 +116,8 @@ pub fn price_list() -> Html {
 	html! {
 		<div class="price_list">
 			<Input price={price.clone()}/>
+			<inference::Inference/>
+			<notebook::RunNotebook/>
 			<List price={price}/>
 		</div>
 	}
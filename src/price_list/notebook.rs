@@ -0,0 +1,244 @@
+use gloo_storage::{errors::StorageError, LocalStorage, Storage};
+use shiren6_core::price_list::{
+	items,
+	notebook::{Entry, Notebook},
+};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use super::inference::{get_observation_str, ObservationForm};
+
+const STORAGE_KEY: &str = "shiren6-utils.notebook";
+
+fn modify(notebook: &UseStateHandle<Notebook>, f: impl FnOnce(&mut Notebook)) {
+	let mut modified = (**notebook).clone();
+	f(&mut modified);
+	notebook.set(modified);
+}
+
+#[derive(PartialEq, Properties)]
+struct EntryViewProperties {
+	notebook: UseStateHandle<Notebook>,
+	index: usize,
+	/// what `Notebook::solve` left for the entry
+	candidates: Vec<&'static str>,
+	/// whether the entry is among `Solution::conflicts`
+	conflict: bool,
+}
+
+#[function_component(EntryView)]
+fn entry_view(props: &EntryViewProperties) -> Html {
+	let index = props.index;
+	let entry = &props.notebook.entries[index];
+	let Some(category) = items::get_category(&entry.category) else {
+		return html! {};
+	};
+
+	let onchange_identified = {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |e: Event| {
+			if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+				let name = Some(select.value()).filter(|name| !name.is_empty());
+				modify(&notebook, |notebook| {
+					notebook.entries[index].identified = name
+				});
+			}
+		})
+	};
+	let onchange_note = {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |e: Event| {
+			if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+				modify(&notebook, |notebook| {
+					notebook.entries[index].note = input.value()
+				});
+			}
+		})
+	};
+	let onadd = {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |observation| {
+			modify(&notebook, |notebook| {
+				let observations = &mut notebook.entries[index].observations;
+				if !observations.contains(&observation) {
+					observations.push(observation);
+				}
+			});
+		})
+	};
+	let onremove_observation = |i: usize| {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |_| {
+			modify(&notebook, |notebook| {
+				notebook.entries[index].observations.remove(i);
+			});
+		})
+	};
+	let onclick_identify = |name: &'static str| {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |_| {
+			modify(&notebook, |notebook| {
+				notebook.entries[index].identified = Some(String::from(name))
+			});
+		})
+	};
+	let onremove = {
+		let notebook = props.notebook.clone();
+
+		Callback::from(move |_| {
+			modify(&notebook, |notebook| {
+				notebook.entries.remove(index);
+			});
+		})
+	};
+
+	let candidates = &props.candidates;
+	let forced = match candidates.as_slice() {
+		&[name] if entry.identified.is_none() && !props.conflict => Some(name),
+		_ => None,
+	};
+
+	html! {
+		<div class="entry">
+			<div>
+				<span class="alias">{format!("{} ({})", entry.alias, category.name)}</span>
+				<select onchange={onchange_identified}>
+					<option value="" selected={entry.identified.is_none()}>{"未識別"}</option>
+					{category.rows.iter().map(|row| html! {
+						<option value={row.name} selected={entry.identified.as_deref() == Some(row.name)}>{row.name}</option>
+					}).collect::<Html>()}
+				</select>
+				<button onclick={onremove}>{"削除"}</button>
+			</div>
+			<ul class="observations">
+			{
+				entry.observations.iter().enumerate().map(|(i, observation)| html! {
+					<li>
+						{get_observation_str(observation)}
+						<button onclick={onremove_observation(i)}>{"×"}</button>
+					</li>
+				}).collect::<Html>()
+			}
+			</ul>
+			<ObservationForm onadd={onadd}/>
+			<input class="note" placeholder="メモ" value={entry.note.clone()} onchange={onchange_note}/>
+			<div class="candidates">
+				if candidates.is_empty() {
+					{"当てはまるアイテムがありません"}
+				} else if props.conflict {
+					<span class="conflict">{"他のアイテムと矛盾しています"}</span>
+					{format!("候補 ({}): {}", candidates.len(), candidates.join("、"))}
+				} else if let Some(name) = forced {
+					<span class="forced">{format!("確定: {name}")}</span>
+					<button onclick={onclick_identify(name)}>{"識別済みにする"}</button>
+				} else {
+					{format!("候補 ({}): {}", candidates.len(), candidates.join("、"))}
+				}
+			</div>
+		</div>
+	}
+}
+
+#[function_component(RunNotebook)]
+pub fn run_notebook() -> Html {
+	// set when a saved notebook could not be read, which is then kept until this one is edited
+	let unreadable = use_mut_ref(|| false);
+	let notebook = {
+		let unreadable = unreadable.clone();
+
+		use_state(move || match LocalStorage::get::<Notebook>(STORAGE_KEY) {
+			Ok(notebook) => notebook,
+			Err(StorageError::SerdeError(_)) => {
+				*unreadable.borrow_mut() = true;
+				Notebook::default()
+			}
+			Err(_) => Notebook::default(),
+		})
+	};
+	let category_ref = use_node_ref();
+	let alias_ref = use_node_ref();
+
+	use_effect_with((*notebook).clone(), move |notebook| {
+		// the first run is the notebook as loaded, and every later one an edit
+		if !unreadable.replace(false) {
+			// nothing to do when storage is unavailable but keeping the notebook for this session
+			let _ = LocalStorage::set(STORAGE_KEY, notebook);
+		}
+	});
+
+	let onsubmit = {
+		let notebook = notebook.clone();
+		let category_ref = category_ref.clone();
+		let alias_ref = alias_ref.clone();
+
+		Callback::from(move |e: SubmitEvent| {
+			e.prevent_default();
+			let (Some(category), Some(input)) = (
+				category_ref.cast::<HtmlSelectElement>(),
+				alias_ref.cast::<HtmlInputElement>(),
+			) else {
+				return;
+			};
+			let alias = input.value().trim().to_owned();
+			if alias.is_empty() {
+				return;
+			}
+			modify(&notebook, |notebook| {
+				notebook.entries.push(Entry::new(&category.value(), &alias))
+			});
+			input.set_value("");
+		})
+	};
+
+	let solution = notebook.solve();
+
+	let onclick_reset = {
+		let notebook = notebook.clone();
+
+		Callback::from(move |_| {
+			let confirmed = web_sys::window()
+				.and_then(|window| {
+					window
+						.confirm_with_message("今の冒険ノートを消して新しい冒険を始めますか？")
+						.ok()
+				})
+				.unwrap_or(false);
+			if confirmed {
+				notebook.set(Notebook::default());
+			}
+		})
+	};
+
+	html! {
+		<div class="notebook">
+			<div class="category">{"冒険ノート"}</div>
+			<button onclick={onclick_reset}>{"新しい冒険"}</button>
+			<form onsubmit={onsubmit}>
+				<select ref={category_ref}>
+				{
+					items::CATEGORIES.iter().map(|category| html! {
+						<option value={category.id}>{category.name}</option>
+					}).collect::<Html>()
+				}
+				</select>
+				<input ref={alias_ref} placeholder="未識別の名前 (例: 赤い草)"/>
+				<button type="submit">{"追加"}</button>
+			</form>
+			{
+				solution.candidates.into_iter().enumerate().map(|(index, candidates)| html! {
+					<EntryView
+						notebook={notebook.clone()}
+						index={index}
+						candidates={candidates}
+						conflict={solution.conflicts.contains(&index)}
+					/>
+				}).collect::<Html>()
+			}
+		</div>
+	}
+}
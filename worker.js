@@ -0,0 +1,8 @@
+import init, { run_worker } from './pkg/shiren6_utils.js';
+async function main() {
+	// relative to the bundled worker script in ./pkg
+	await init('./shiren6_utils_bg.wasm');
+	run_worker();
+}
+// rethrown outside the promise so that the page sees it as the worker's error event
+main().catch((e) => setTimeout(() => { throw e; }));